@@ -0,0 +1,58 @@
+//! Counts the heap allocations made while parsing a generated recording.
+//!
+//! Run with `cargo bench --bench allocations`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use tacview::Parser;
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn main() {
+    let mut acmi = String::from("FileType=text/acmi/tacview\nFileVersion=2.2\n");
+    for frame in 0..10_000 {
+        writeln!(acmi, "#{}", frame as f64 * 0.2).unwrap();
+        for id in 1..=10 {
+            writeln!(acmi, "{id:x},T={frame}|{id}|1000").unwrap();
+        }
+    }
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let mut records = 0;
+    for record in Parser::new(acmi.as_bytes()).unwrap() {
+        record.unwrap();
+        records += 1;
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    println!(
+        "parsed {records} records in {elapsed:?}: {allocations} allocations ({:.2} per record)",
+        allocations as f64 / records as f64
+    );
+}
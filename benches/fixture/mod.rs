@@ -0,0 +1,81 @@
+//! Generates a representative recording to benchmark against: a few aircraft with full
+//! coordinates and flight data, ground units that barely change, missiles that are fired and
+//! removed again, and the occasional event.
+
+use std::fmt::Write;
+
+/// Generates a recording of `frames` frames (at 5 Hz). About 5 MB for 10,000 frames.
+pub fn recording(frames: u32) -> String {
+    let mut acmi = String::from("FileType=text/acmi/tacview\nFileVersion=2.2\n");
+    acmi.push_str("0,DataSource=Benchmark\n0,ReferenceTime=2024-08-02T10:00:00Z\n");
+    acmi.push_str("0,ReferenceLongitude=41\n0,ReferenceLatitude=41\n");
+
+    for frame in 0..frames {
+        let t = frame as f64 * 0.2;
+        writeln!(acmi, "#{t}").unwrap();
+
+        for id in 1..=8u64 {
+            let (lon, lat) = (0.1 * id as f64 + t * 0.0005, 0.2 + t * 0.0003);
+            if frame == 0 {
+                writeln!(
+                    acmi,
+                    "{id:x},T={lon:.7}|{lat:.7}|{:.2}|{:.1}|{:.1}|{:.1},Type=Air+FixedWing,\
+                     Name=F-16C_50,Pilot=Pilot {id},Group=Flight {},Coalition=Allies,Color=Blue",
+                    5000.0 + id as f64,
+                    0.0,
+                    2.5,
+                    90.0,
+                    id / 4 + 1,
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    acmi,
+                    "{id:x},T={lon:.7}|{lat:.7}|{:.2}|{:.1}|{:.1}|{:.1},IAS={:.2},AOA={:.2}",
+                    5000.0 + id as f64 + (t * 0.1).sin() * 50.0,
+                    (t * 0.3).sin() * 10.0,
+                    2.5,
+                    90.0 + (t * 0.05).sin() * 5.0,
+                    250.0 + (t * 0.2).cos(),
+                    3.0 + (t * 0.4).sin(),
+                )
+                .unwrap();
+            }
+        }
+
+        for id in 0x100..0x104u64 {
+            if frame == 0 {
+                writeln!(
+                    acmi,
+                    "{id:x},T=0.5|0.5|10,Type=Ground+AntiAircraft,Name=SA-11 Buk LN 9A310M1,\
+                     Coalition=Enemies,Color=Red,RadarMode=1"
+                )
+                .unwrap();
+            } else if frame % 50 == 0 {
+                writeln!(acmi, "{id:x},RadarMode={}", (frame / 50) % 2).unwrap();
+            }
+        }
+
+        // a missile every 10 s, flying for 5 s
+        let missile = 0x1000 + (frame / 50) as u64;
+        match frame % 50 {
+            0 => {
+                writeln!(
+                    acmi,
+                    "{missile:x},T=0.1|0.2|5000|0|0|90,Type=Weapon+Missile,Name=AIM-120C,Parent=1"
+                )
+                .unwrap();
+            }
+            1..=24 => {
+                writeln!(acmi, "{missile:x},T={:.7}|0.2|5000", 0.1 + t * 0.001).unwrap();
+            }
+            25 => {
+                writeln!(acmi, "0,Event=Destroyed|{missile:x}|").unwrap();
+                writeln!(acmi, "-{missile:x}").unwrap();
+            }
+            _ => {}
+        }
+    }
+
+    acmi
+}
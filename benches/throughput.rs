@@ -0,0 +1,57 @@
+//! Throughput of parsing and writing a generated recording.
+//!
+//! Run with `cargo bench --bench throughput`.
+
+mod fixture;
+
+use std::hint::black_box;
+use std::str::FromStr;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use tacview::record::{Record, Update};
+use tacview::{Parser, Writer};
+
+fn parse(c: &mut Criterion) {
+    let acmi = fixture::recording(10_000);
+
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Bytes(acmi.len() as u64));
+    group.bench_function("parser", |b| {
+        b.iter(|| {
+            for record in Parser::new(acmi.as_bytes()).unwrap() {
+                black_box(record.unwrap());
+            }
+        })
+    });
+    group.finish();
+
+    let line = "1,T=0.1234567|0.2345678|5012.34|-3.2|2.5|91.3,IAS=250.12,AOA=3.45";
+    let mut group = c.benchmark_group("update");
+    group.throughput(Throughput::Bytes(line.len() as u64));
+    group.bench_function("from_str", |b| {
+        b.iter(|| Update::from_str(black_box(line)).unwrap())
+    });
+    group.finish();
+}
+
+fn write(c: &mut Criterion) {
+    let acmi = fixture::recording(10_000);
+    let records = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .collect::<Result<Vec<Record>, _>>()
+        .unwrap();
+
+    let mut group = c.benchmark_group("write");
+    group.throughput(Throughput::Bytes(acmi.len() as u64));
+    group.bench_function("writer", |b| {
+        b.iter(|| {
+            let mut wr = Writer::new(Vec::with_capacity(acmi.len())).unwrap();
+            wr.write_all(records.iter().cloned()).unwrap();
+            black_box(wr.into_inner())
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse, write);
+criterion_main!(benches);
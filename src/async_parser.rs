@@ -0,0 +1,279 @@
+use std::io;
+use std::mem;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use futures_core::Stream;
+use tokio::io::AsyncBufRead;
+
+use crate::parser::{check_file_type, check_version, is_banner_line, lines, parse_line};
+use crate::record::Record;
+use crate::ParseError;
+
+/// Asynchronous counterpart of [`crate::Parser`], yielding records from an [`AsyncBufRead`].
+pub struct AsyncParser<R> {
+    rd: R,
+    buf: Vec<u8>,
+    splitter: lines::LineSplitter,
+    line: usize,
+    /// The number of the first physical line of the line that is currently buffered.
+    start_line: usize,
+    state: State,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    FileType,
+    Version,
+    Records,
+    Done,
+}
+
+impl<R> AsyncParser<R>
+where
+    R: AsyncBufRead + Unpin,
+{
+    /// Creates a new parser. The file type and version are checked while polling the first
+    /// records.
+    pub fn new(rd: R) -> Self {
+        Self {
+            rd,
+            buf: Vec::new(),
+            splitter: lines::LineSplitter::default(),
+            line: 0,
+            start_line: 1,
+            state: State::FileType,
+        }
+    }
+
+    fn poll_line(&mut self, cx: &mut Context<'_>) -> Poll<Option<io::Result<String>>> {
+        loop {
+            let available = match ready!(Pin::new(&mut self.rd).poll_fill_buf(cx)) {
+                Ok(available) => available,
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            };
+
+            if available.is_empty() {
+                if self.buf.is_empty() {
+                    return Poll::Ready(None);
+                }
+                let line = into_string(mem::take(&mut self.buf)).map(|mut line| {
+                    lines::finish_last_line(&mut line);
+                    line
+                });
+                return Poll::Ready(Some(line));
+            }
+
+            if self.buf.is_empty() {
+                self.start_line = self.line + 1;
+            }
+            let (n, complete) = self.splitter.split(available, &mut self.buf);
+            Pin::new(&mut self.rd).consume(n);
+            if !complete {
+                continue;
+            }
+            self.line += 1;
+
+            let mut line = match into_string(mem::take(&mut self.buf)) {
+                Ok(line) => line,
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            };
+            if lines::finish_line(&mut line) {
+                return Poll::Ready(Some(Ok(line)));
+            }
+            self.buf = line.into_bytes();
+        }
+    }
+}
+
+impl<R> Stream for AsyncParser<R>
+where
+    R: AsyncBufRead + Unpin,
+{
+    type Item = Result<Record, ParseError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.state == State::Done {
+                return Poll::Ready(None);
+            }
+
+            let line = match ready!(this.poll_line(cx)) {
+                Some(Ok(line)) => line,
+                Some(Err(err)) => {
+                    this.state = State::Done;
+                    return Poll::Ready(Some(Err(ParseError::Io(err))));
+                }
+                None => {
+                    let state = mem::replace(&mut this.state, State::Done);
+                    return Poll::Ready(match state {
+                        State::FileType => Some(Err(ParseError::InvalidFileType)),
+                        State::Version => Some(Err(ParseError::InvalidVersion)),
+                        State::Records | State::Done => None,
+                    });
+                }
+            };
+
+            match this.state {
+                State::FileType if is_banner_line(&line) => {}
+                State::FileType => {
+                    if let Err(err) = check_file_type(&line) {
+                        this.state = State::Done;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    this.state = State::Version;
+                }
+                State::Version => {
+                    if let Err(err) = check_version(&line) {
+                        this.state = State::Done;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                    this.state = State::Records;
+                }
+                State::Records => {
+                    if line.is_empty() {
+                        this.state = State::Done;
+                        return Poll::Ready(None);
+                    }
+
+                    match parse_line(&line) {
+                        Ok(Some(record)) => return Poll::Ready(Some(Ok(record))),
+                        Ok(None) => {}
+                        Err(err) => {
+                            return Poll::Ready(Some(Err(ParseError::At {
+                                line: this.start_line,
+                                source: Box::new(err),
+                            })))
+                        }
+                    }
+                }
+                State::Done => unreachable!(),
+            }
+        }
+    }
+}
+
+fn into_string(buf: Vec<u8>) -> io::Result<String> {
+    String::from_utf8(buf).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+
+    use super::*;
+    use crate::record::GlobalProperty;
+    use crate::Parser;
+
+    #[tokio::test]
+    async fn test_async_parser() {
+        let acmi = "FileType=text/acmi/tacview\r\nFileVersion=2.2\r\n0,Comments=1\\\r\n2\r\n#1\r\n1,T=1|2|3\r\n";
+        let mut stream = AsyncParser::new(acmi.as_bytes());
+        let mut records = Vec::new();
+        while let Some(record) = stream.next().await {
+            records.push(record.unwrap());
+        }
+        assert_eq!(
+            records[0],
+            Record::GlobalProperty(GlobalProperty::Comments("1\r\n2".to_string()))
+        );
+
+        let expected = Parser::new(acmi.as_bytes())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(records, expected);
+
+        // bare carriage returns (classic Mac OS line endings)
+        let acmi = "FileType=text/acmi/tacview\rFileVersion=2.2\r0,Comments=1\\\r2\r#1\r-2a\r";
+        let records = AsyncParser::new(acmi.as_bytes())
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            records,
+            vec![
+                Record::GlobalProperty(GlobalProperty::Comments("1\r2".to_string())),
+                Record::Frame(1.0),
+                Record::Remove(0x2a),
+            ]
+        );
+    }
+
+    /// Yields each chunk separately, and returns `Pending` before each chunk.
+    struct ChunkedReader {
+        chunks: Vec<&'static [u8]>,
+        pending: bool,
+    }
+
+    impl tokio::io::AsyncRead for ChunkedReader {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            _buf: &mut tokio::io::ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            unimplemented!("only read via AsyncBufRead")
+        }
+    }
+
+    impl AsyncBufRead for ChunkedReader {
+        fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+            let this = self.get_mut();
+            if this.chunks.first().is_some_and(|chunk| chunk.is_empty()) {
+                this.chunks.remove(0);
+                this.pending = true;
+            }
+            if this.pending {
+                this.pending = false;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            Poll::Ready(Ok(this.chunks.first().copied().unwrap_or_default()))
+        }
+
+        fn consume(self: Pin<&mut Self>, amt: usize) {
+            let chunk = &mut self.get_mut().chunks[0];
+            *chunk = &chunk[amt..];
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_parser_error_line_across_pending() {
+        let chunks: Vec<&'static [u8]> = vec![
+            b"FileType=text/acmi/tacview\nFileVersion=2.2\n1,Name=a\\\n",
+            b"b\\\n",
+            b"c,Parent=zz\n",
+        ];
+        let stream = AsyncParser::new(ChunkedReader {
+            chunks: chunks.clone(),
+            pending: true,
+        });
+        let records = stream.collect::<Vec<_>>().await;
+        assert!(matches!(
+            records.as_slice(),
+            [Err(ParseError::At { line: 3, .. })]
+        ));
+
+        let expected = Parser::new(chunks.concat().as_slice())
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            expected.as_slice(),
+            [Err(ParseError::At { line: 3, .. })]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_async_parser_invalid_version() {
+        let acmi = "FileType=text/acmi/tacview\nFileVersion=1.0\n#1\n";
+        let mut stream = AsyncParser::new(acmi.as_bytes());
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(ParseError::InvalidVersion))
+        ));
+        assert!(stream.next().await.is_none());
+    }
+}
@@ -0,0 +1,86 @@
+//! The bounding box of all object coordinates of a recording, e.g. to center a map on it.
+
+use std::io::Read;
+
+use crate::record::{Coords, Property, Record};
+use crate::{ParseError, Parser};
+
+/// The minimum and maximum of each coordinate component. Components no coordinate had a value for
+/// are `None`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    /// Unit: deg
+    pub min_lat: Option<f64>,
+    /// Unit: deg
+    pub max_lat: Option<f64>,
+    /// Unit: deg
+    pub min_lon: Option<f64>,
+    /// Unit: deg
+    pub max_lon: Option<f64>,
+    /// Unit: m
+    pub min_alt: Option<f64>,
+    /// Unit: m
+    pub max_alt: Option<f64>,
+}
+
+impl BoundingBox {
+    /// Extends the bounding box to include the (absolute) coordinates. Only the components present
+    /// in `coords` are taken into account.
+    pub fn extend(&mut self, coords: &Coords) {
+        extend(&mut self.min_lat, &mut self.max_lat, coords.latitude);
+        extend(&mut self.min_lon, &mut self.max_lon, coords.longitude);
+        extend(&mut self.min_alt, &mut self.max_alt, coords.altitude);
+    }
+}
+
+fn extend(min: &mut Option<f64>, max: &mut Option<f64>, value: Option<f64>) {
+    let Some(value) = value.filter(|v| !v.is_nan()) else {
+        return;
+    };
+    *min = Some(min.map_or(value, |min| min.min(value)));
+    *max = Some(max.map_or(value, |max| max.max(value)));
+}
+
+/// Computes the bounding box of all object coordinates of the recording, with the reference
+/// offsets applied.
+pub fn bounding_box<R: Read>(parser: Parser<R>) -> Result<BoundingBox, ParseError> {
+    let mut bounds = BoundingBox::default();
+    for record in parser.with_reference_resolution() {
+        if let Record::Update(update) = record? {
+            for prop in &update.props {
+                if let Property::T(coords) = prop {
+                    bounds.extend(coords);
+                }
+            }
+        }
+    }
+    Ok(bounds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounding_box() {
+        let acmi = "FileType=text/acmi/tacview\nFileVersion=2.2\n\
+                    0,ReferenceLongitude=10\n0,ReferenceLatitude=20\n\
+                    #0\n1,T=1|2|300\n2,T=-1|4|\n#1\n1,T=|3|\n2,T=|1|50\n";
+        let bounds = bounding_box(Parser::new(acmi.as_bytes()).unwrap()).unwrap();
+        assert_eq!(
+            bounds,
+            BoundingBox {
+                min_lat: Some(21.0),
+                max_lat: Some(24.0),
+                min_lon: Some(9.0),
+                max_lon: Some(11.0),
+                min_alt: Some(50.0),
+                max_alt: Some(300.0),
+            }
+        );
+
+        let acmi = "FileType=text/acmi/tacview\nFileVersion=2.2\n#0\n1,Name=F-16C\n";
+        let bounds = bounding_box(Parser::new(acmi.as_bytes()).unwrap()).unwrap();
+        assert_eq!(bounds, BoundingBox::default());
+    }
+}
@@ -0,0 +1,93 @@
+//! Counts the objects of a recording by name and type, e.g. for mission reports.
+
+use std::collections::{BTreeSet, HashMap};
+use std::io::Read;
+
+use crate::record::{Property, Record, Tag};
+use crate::{ParseError, Parser};
+
+/// The number of (unique) objects per name and per type tag of a recording.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Census {
+    /// The number of objects per [`Property::Name`]. Objects without a name are not counted.
+    pub by_name: HashMap<String, usize>,
+    /// The number of objects per tag of their [`Property::Type`], e.g. [`Tag::Air`] or
+    /// [`Tag::AntiAircraft`]. Objects are counted once for each of their tags.
+    pub by_tag: HashMap<Tag, usize>,
+}
+
+#[derive(Default)]
+struct Object {
+    name: Option<String>,
+    tags: BTreeSet<Tag>,
+}
+
+impl Census {
+    fn count(&mut self, object: Object) {
+        if let Some(name) = object.name {
+            *self.by_name.entry(name).or_default() += 1;
+        }
+        for tag in object.tags {
+            *self.by_tag.entry(tag).or_default() += 1;
+        }
+    }
+}
+
+/// Counts the objects of the recording. Each object is counted once, with the last name and type
+/// it had before it was removed (or the recording ended), no matter in which update they were
+/// set. An id that is reused after its object was removed is counted as a new object.
+pub fn census<R: Read>(parser: Parser<R>) -> Result<Census, ParseError> {
+    let mut census = Census::default();
+    let mut objects = HashMap::<u64, Object>::new();
+    for record in parser {
+        match record? {
+            Record::Update(update) => {
+                let object = objects.entry(update.id).or_default();
+                for prop in update.props {
+                    match prop {
+                        Property::Name(name) => object.name = Some(name),
+                        Property::Type(tags) => object.tags = tags,
+                        _ => {}
+                    }
+                }
+            }
+            Record::Remove(id) => {
+                if let Some(object) = objects.remove(&id) {
+                    census.count(object);
+                }
+            }
+            Record::GlobalProperty(_) | Record::Event(_) | Record::Frame(_) => {}
+        }
+    }
+    for object in objects.into_values() {
+        census.count(object);
+    }
+    Ok(census)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_census() {
+        let acmi = "FileType=text/acmi/tacview\nFileVersion=2.2\n\
+                    #0\n1,T=1|2|3,Name=F-16C,Type=Air+FixedWing\n2,T=1|2|3\n3,Name=SA-11\n\
+                    #1\n1,T=1|2|4\n2,Name=F-16C,Type=Air+FixedWing\n3,Type=Ground+AntiAircraft\n\
+                    #2\n-2\n2,Name=AIM-120C,Type=Weapon+Missile\n";
+        let census = census(Parser::new(acmi.as_bytes()).unwrap()).unwrap();
+        assert_eq!(
+            census.by_name,
+            HashMap::from([
+                ("F-16C".to_string(), 2),
+                ("SA-11".to_string(), 1),
+                ("AIM-120C".to_string(), 1)
+            ])
+        );
+        assert_eq!(census.by_tag[&Tag::Air], 2);
+        assert_eq!(census.by_tag[&Tag::FixedWing], 2);
+        assert_eq!(census.by_tag[&Tag::AntiAircraft], 1);
+        assert_eq!(census.by_tag[&Tag::Missile], 1);
+        assert_eq!(census.by_tag.len(), 6);
+    }
+}
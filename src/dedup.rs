@@ -0,0 +1,60 @@
+//! Drops redundant object properties from a recording, i.e. properties that do not change the
+//! state of their object, like a name repeated every frame.
+
+use std::io::Read;
+
+use crate::record::Record;
+use crate::{ParseError, Parser, StateTracker};
+
+/// Yields the records of `parser` with all object properties removed that would not change the
+/// state of their object, see [`StateTracker::diff`]. Coordinates are compared component-wise.
+/// Updates without any remaining properties are dropped entirely, all other records are yielded as
+/// is.
+///
+/// The parser must not resolve references (see [`Parser::with_reference_resolution`]), as the
+/// yielded updates are relative to the reference point just like the input.
+pub fn dedup<R: Read>(parser: Parser<R>) -> impl Iterator<Item = Result<Record, ParseError>> {
+    let mut tracker = StateTracker::new();
+    parser.filter_map(move |record| {
+        let record = match record {
+            Ok(record) => record,
+            Err(err) => return Some(Err(err)),
+        };
+        if let Record::Update(update) = &record {
+            let diff = tracker.diff(update.id, update);
+            tracker.apply(&record);
+            return (!diff.props.is_empty()).then_some(Ok(Record::Update(diff)));
+        }
+        tracker.apply(&record);
+        Some(Ok(record))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Writer;
+
+    #[test]
+    fn test_dedup() {
+        let acmi = "FileType=text/acmi/tacview\nFileVersion=2.2\n\
+                    0,ReferenceLongitude=10\n\
+                    #0\n1,T=1|2|3,Name=F-16C,IAS=100\n\
+                    #1\n1,T=1|2.5|3,Name=F-16C,IAS=100\n\
+                    #2\n1,T=1|2.5|3,Name=F-16C,IAS=100\n-1\n\
+                    #3\n1,T=1|2.5|3,Name=F-16C\n";
+        let mut wr = Writer::new(Vec::new()).unwrap();
+        for record in dedup(Parser::new(acmi.as_bytes()).unwrap()) {
+            wr.write(record.unwrap()).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(wr.into_inner()).unwrap(),
+            "FileType=text/acmi/tacview\nFileVersion=2.2\n\
+             0,ReferenceLongitude=10\n\
+             #0\n1,T=1|2|3,Name=F-16C,IAS=100\n\
+             #1\n1,T=|2.5|\n\
+             #2\n-1\n\
+             #3\n1,T=1|2.5|3,Name=F-16C\n"
+        );
+    }
+}
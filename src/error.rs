@@ -0,0 +1,56 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+
+use crate::record::CoordError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError {
+    #[error("input is not a ACMI file")]
+    InvalidFileType,
+    #[error("invalid version, expected ACMI v2.x")]
+    InvalidVersion,
+    #[cfg(feature = "std")]
+    #[error("error reading input")]
+    Io(#[from] std::io::Error),
+    #[error("unexpected end of line")]
+    Eol,
+    #[error("{field} `{value}` is not a valid integer")]
+    InvalidId {
+        /// What the value is, e.g. `object id` or the name of the property.
+        field: &'static str,
+        value: String,
+        source: core::num::ParseIntError,
+    },
+    #[error("{field} `{value}` is not numeric")]
+    InvalidNumeric {
+        /// What the value is, e.g. `frame time`, the name of the property or the component of
+        /// its coordinates.
+        field: &'static str,
+        value: String,
+        source: core::num::ParseFloatError,
+    },
+    #[error("could not find expected delimiter `{0}`")]
+    MissingDelimiter(char),
+    #[error("failed to parse event")]
+    InvalidEvent,
+    #[error("line is a comment")]
+    Comment,
+    #[error("unknown event `{0}`")]
+    UnknownEvent(String),
+    #[error("property `{0}` is set more than once")]
+    DuplicateProperty(String),
+    #[error("encountered invalid coordinate format")]
+    InvalidCoordinateFormat,
+    #[error("invalid coordinates")]
+    InvalidCoordinates(#[from] CoordError),
+    #[error("frame time {time} is before the previous frame time {previous}")]
+    NonMonotonicFrame { previous: f64, time: f64 },
+    #[cfg(feature = "std")]
+    #[error("error reading zip compressed input")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("failed to parse line {line}")]
+    At {
+        line: usize,
+        source: Box<ParseError>,
+    },
+}
@@ -0,0 +1,188 @@
+//! Flattens a recording into CSV with one row per frame and object.
+
+use std::fmt::Display;
+use std::io::Write;
+
+use crate::export::ExportError;
+use crate::record::{Property, Record};
+use crate::state::ObjectState;
+use crate::{ParseError, StateTracker};
+
+const COLUMNS: &[&str] = &[
+    "id",
+    "time",
+    "longitude",
+    "latitude",
+    "altitude",
+    "roll",
+    "pitch",
+    "yaw",
+    "heading",
+    "name",
+    "type",
+    "callsign",
+    "pilot",
+    "coalition",
+    "color",
+    "ias",
+    "tas",
+    "agl",
+];
+
+/// Writes the state of all objects alive at each frame as CSV, i.e. their coordinates and a few
+/// common properties. Object ids are written in hex, as in ACMI files. Properties an object does
+/// not have (yet) are left empty.
+///
+/// The records must contain relative coordinates, i.e. come from a [`crate::Parser`] without
+/// [`crate::Parser::with_reference_resolution`], as the reference offsets are applied while
+/// exporting.
+pub fn export<W: Write>(
+    records: impl IntoIterator<Item = Result<Record, ParseError>>,
+    mut wr: W,
+) -> Result<(), ExportError> {
+    writeln!(wr, "{}", COLUMNS.join(","))?;
+
+    let mut tracker = StateTracker::new();
+    let mut frame_seen = false;
+    for record in records {
+        let record = record?;
+        if let Record::Frame(_) = record {
+            // objects updated before the first frame are written as part of the first frame
+            if frame_seen {
+                write_frame(&mut wr, &tracker)?;
+            }
+            frame_seen = true;
+        }
+        tracker.apply(&record);
+    }
+    write_frame(&mut wr, &tracker)?;
+
+    Ok(())
+}
+
+fn write_frame(wr: &mut impl Write, tracker: &StateTracker) -> Result<(), ExportError> {
+    let mut objects = tracker.objects().collect::<Vec<_>>();
+    objects.sort_by_key(|object| object.id);
+
+    for object in objects {
+        write_row(wr, tracker.time(), object)?;
+    }
+    Ok(())
+}
+
+fn write_row(wr: &mut impl Write, time: f64, object: &ObjectState) -> Result<(), ExportError> {
+    let coords = &object.coords;
+    write!(wr, "{:x},{time}", object.id)?;
+    for v in [
+        coords.longitude,
+        coords.latitude,
+        coords.altitude,
+        coords.roll,
+        coords.pitch,
+        coords.yaw,
+        coords.heading,
+    ] {
+        write!(wr, ",{}", Cell(v))?;
+    }
+
+    let text = |f: fn(&Property) -> Option<String>| Cell(object.find(f));
+    write!(
+        wr,
+        ",{},{},{},{},{},{}",
+        text(|p| match p {
+            Property::Name(v) => Some(v.clone()),
+            _ => None,
+        }),
+        text(|p| match p {
+            Property::Type(tags) => Some(
+                tags.iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("+"),
+            ),
+            _ => None,
+        }),
+        text(|p| match p {
+            Property::CallSign(v) => Some(v.clone()),
+            _ => None,
+        }),
+        text(|p| match p {
+            Property::Pilot(v) => Some(v.clone()),
+            _ => None,
+        }),
+        text(|p| match p {
+            Property::Coalition(v) => Some(v.to_string()),
+            _ => None,
+        }),
+        text(|p| match p {
+            Property::Color(v) => Some(v.to_string()),
+            _ => None,
+        }),
+    )?;
+
+    writeln!(
+        wr,
+        ",{},{},{}",
+        Cell(object.find(|p| match p {
+            Property::IAS(v) => Some(*v),
+            _ => None,
+        })),
+        Cell(object.find(|p| match p {
+            Property::TAS(v) => Some(*v),
+            _ => None,
+        })),
+        Cell(object.find(|p| match p {
+            Property::AGL(v) => Some(*v),
+            _ => None,
+        })),
+    )?;
+    Ok(())
+}
+
+/// A CSV cell, empty for `None` and quoted if necessary.
+struct Cell<T>(Option<T>);
+
+impl<T: Display> Display for Cell<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(v) = &self.0 else {
+            return Ok(());
+        };
+        let v = v.to_string();
+        if v.contains([',', '"', '\n', '\r']) {
+            write!(f, "\"{}\"", v.replace('"', "\"\""))
+        } else {
+            f.write_str(&v)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+
+    #[test]
+    fn test_export() {
+        let acmi = r#"FileType=text/acmi/tacview
+FileVersion=2.2
+0,ReferenceLongitude=10
+0,ReferenceLatitude=20
+#0
+1,T=1|2|3,Name=F-16C,Type=Air+FixedWing,Coalition=Allies,IAS=100
+2,T=4|5|6,Name=Tank\, "Leo"
+#1.5
+1,T=2||
+-2
+"#;
+        let mut csv = Vec::new();
+        export(Parser::new(acmi.as_bytes()).unwrap(), &mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "id,time,longitude,latitude,altitude,roll,pitch,yaw,heading,name,type,callsign,pilot,coalition,color,ias,tas,agl
+1,0,11,22,3,,,,,F-16C,Air+FixedWing,,,Allies,,100,,
+2,0,14,25,6,,,,,\"Tank, \"\"Leo\"\"\",,,,,,,,
+1,1.5,12,22,3,,,,,F-16C,Air+FixedWing,,,Allies,,100,,
+"
+        );
+    }
+}
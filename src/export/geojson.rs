@@ -0,0 +1,131 @@
+//! Converts a recording into a GeoJSON `FeatureCollection` with the track of each object.
+
+use serde_json::{json, Map, Value};
+
+use crate::export::{ExportError, Track, TrackCollector};
+use crate::record::{Property, Record};
+use crate::ParseError;
+
+/// Returns a `FeatureCollection` with one feature per object, in the order the objects appeared.
+/// Each feature's geometry is a `LineString` of the object's absolute WGS84 positions (a `Point`
+/// if the object only had a single position), and its properties contain the object's `id` (in
+/// hex), the `times` (in seconds) of all positions, as well as its final `name`, `type` tags and
+/// `coalition`. Tracks of objects removed during the recording end with their last position.
+///
+/// The records must contain relative coordinates, i.e. come from a [`crate::Parser`] without
+/// [`crate::Parser::with_reference_resolution`], as the reference offsets are applied while
+/// exporting.
+pub fn export(
+    records: impl IntoIterator<Item = Result<Record, ParseError>>,
+) -> Result<Value, ExportError> {
+    let mut collector = TrackCollector::new();
+    for record in records {
+        collector.apply(&record?, |time, longitude, latitude, altitude| {
+            Ok::<_, ExportError>(match altitude {
+                Some(altitude) => (time, vec![longitude, latitude, altitude]),
+                None => (time, vec![longitude, latitude]),
+            })
+        })?;
+    }
+
+    Ok(json!({
+        "type": "FeatureCollection",
+        "features": collector.into_tracks().into_iter().map(into_feature).collect::<Vec<_>>(),
+    }))
+}
+
+/// Converts a track of `(time, position)` pairs into a feature.
+fn into_feature(track: Track<(f64, Vec<f64>)>) -> Value {
+    let mut properties = Map::new();
+    properties.insert("id".to_string(), json!(format!("{:x}", track.id)));
+    let (times, mut positions): (Vec<_>, Vec<_>) = track.positions.into_iter().unzip();
+    properties.insert("times".to_string(), json!(times));
+    for prop in &track.props {
+        match prop {
+            Property::Name(name) => {
+                properties.insert("name".to_string(), json!(name));
+            }
+            Property::Type(tags) => {
+                let tags = tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+                properties.insert("type".to_string(), json!(tags));
+            }
+            Property::Coalition(coalition) => {
+                properties.insert("coalition".to_string(), json!(coalition.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    let geometry = if positions.len() == 1 {
+        json!({ "type": "Point", "coordinates": positions.remove(0) })
+    } else {
+        json!({ "type": "LineString", "coordinates": positions })
+    };
+
+    json!({
+        "type": "Feature",
+        "geometry": geometry,
+        "properties": properties,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+
+    #[test]
+    fn test_export() {
+        let acmi = r#"FileType=text/acmi/tacview
+FileVersion=2.2
+0,ReferenceLongitude=10
+0,ReferenceLatitude=20
+#0
+1,T=1|2|3,Name=F-16C,Type=Air+FixedWing,Coalition=Allies
+2,T=4|5|,Name=AIM-9
+#1
+1,T=2||
+2,T=5|6|
+#2
+2,T=6|7|
+-2
+1,Name=F-16CM
+3,Name=Bullseye
+"#;
+        let geojson = export(Parser::new(acmi.as_bytes()).unwrap()).unwrap();
+        assert_eq!(
+            geojson,
+            json!({
+                "type": "FeatureCollection",
+                "features": [
+                    {
+                        "type": "Feature",
+                        "geometry": {
+                            "type": "LineString",
+                            "coordinates": [[11.0, 22.0, 3.0], [12.0, 22.0, 3.0]],
+                        },
+                        "properties": {
+                            "id": "1",
+                            "times": [0.0, 1.0],
+                            "name": "F-16CM",
+                            "type": ["Air", "FixedWing"],
+                            "coalition": "Allies",
+                        },
+                    },
+                    {
+                        "type": "Feature",
+                        "geometry": {
+                            "type": "LineString",
+                            "coordinates": [[14.0, 25.0], [15.0, 26.0], [16.0, 27.0]],
+                        },
+                        "properties": {
+                            "id": "2",
+                            "times": [0.0, 1.0, 2.0],
+                            "name": "AIM-9",
+                        },
+                    },
+                ],
+            })
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Converts a recording into KML with an animated `gx:Track` per object, e.g. to replay it in
+//! Google Earth.
+
+use std::fmt::Display;
+use std::io::Write;
+
+use chrono::{DateTime, SecondsFormat, TimeDelta, Utc};
+
+use crate::export::{ExportError, Track, TrackCollector};
+use crate::record::{GlobalProperty, Property, Record};
+use crate::ParseError;
+
+/// Writes a KML document with one `Placemark` per object, in the order the objects appeared.
+/// Each placemark is named after the object's final name (or its id in hex) and contains a
+/// `gx:Track` of the object's absolute WGS84 positions, each with its absolute time (the
+/// `ReferenceTime` plus the frame time). Tracks are `absolute` altitude tracks, or
+/// `clampToGround` if the object lacks an altitude for any of its positions.
+///
+/// Fails with [`ExportError::MissingReferenceTime`] if an object is positioned before a
+/// (well-formed) `ReferenceTime` global property has been read.
+///
+/// The records must contain relative coordinates, i.e. come from a [`crate::Parser`] without
+/// [`crate::Parser::with_reference_resolution`], as the reference offsets are applied while
+/// exporting.
+pub fn export<W: Write>(
+    records: impl IntoIterator<Item = Result<Record, ParseError>>,
+    mut wr: W,
+) -> Result<(), ExportError> {
+    let mut reference_time: Option<DateTime<Utc>> = None;
+    let mut collector = TrackCollector::new();
+    for record in records {
+        let record = record?;
+        if let Record::GlobalProperty(p @ GlobalProperty::ReferenceTime(_)) = &record {
+            reference_time = p.reference_time();
+        }
+        collector.apply(&record, |time, longitude, latitude, altitude| {
+            let offset = TimeDelta::microseconds((time * 1_000_000.0).round() as i64);
+            let time = reference_time
+                .and_then(|t| t.checked_add_signed(offset))
+                .ok_or(ExportError::MissingReferenceTime)?;
+            Ok::<_, ExportError>((time, longitude, latitude, altitude))
+        })?;
+    }
+
+    writeln!(wr, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        wr,
+        r#"<kml xmlns="http://www.opengis.net/kml/2.2" xmlns:gx="http://www.google.com/kml/ext/2.2">"#
+    )?;
+    writeln!(wr, "<Document>")?;
+    for track in collector.into_tracks() {
+        write_track(&track, &mut wr)?;
+    }
+    writeln!(wr, "</Document>")?;
+    writeln!(wr, "</kml>")?;
+
+    Ok(())
+}
+
+/// Writes a `Placemark` with the `gx:Track` of `(time, longitude, latitude, altitude)` positions.
+fn write_track(
+    track: &Track<(DateTime<Utc>, f64, f64, Option<f64>)>,
+    wr: &mut impl Write,
+) -> Result<(), ExportError> {
+    let absolute = track.positions.iter().all(|(.., alt)| alt.is_some());
+    let name = track.props.iter().find_map(|p| match p {
+        Property::Name(name) => Some(name),
+        _ => None,
+    });
+
+    writeln!(wr, "<Placemark>")?;
+    match name {
+        Some(name) => writeln!(wr, "<name>{}</name>", Xml(name))?,
+        None => writeln!(wr, "<name>{:x}</name>", track.id)?,
+    }
+    writeln!(wr, "<gx:Track>")?;
+    writeln!(
+        wr,
+        "<altitudeMode>{}</altitudeMode>",
+        if absolute {
+            "absolute"
+        } else {
+            "clampToGround"
+        }
+    )?;
+    for (time, ..) in &track.positions {
+        writeln!(
+            wr,
+            "<when>{}</when>",
+            time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+        )?;
+    }
+    for (_, longitude, latitude, altitude) in &track.positions {
+        let altitude = altitude.filter(|_| absolute).unwrap_or(0.0);
+        writeln!(wr, "<gx:coord>{longitude} {latitude} {altitude}</gx:coord>")?;
+    }
+    writeln!(wr, "</gx:Track>")?;
+    writeln!(wr, "</Placemark>")?;
+    Ok(())
+}
+
+/// Escapes the characters of a text that are special in XML.
+struct Xml<'a>(&'a str);
+
+impl Display for Xml<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for ch in self.0.chars() {
+            match ch {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&apos;")?,
+                ch => std::fmt::Write::write_char(f, ch)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+
+    #[test]
+    fn test_export() {
+        let acmi = r#"FileType=text/acmi/tacview
+FileVersion=2.2
+0,ReferenceTime=2024-08-02T10:00:00Z
+0,ReferenceLongitude=10
+0,ReferenceLatitude=20
+#0
+1,T=1|2|3,Name=F-16C
+2,T=4|5|,Name=<Tank & Co>
+#1.5
+1,T=2||
+-2
+"#;
+        let mut kml = Vec::new();
+        export(Parser::new(acmi.as_bytes()).unwrap(), &mut kml).unwrap();
+        assert_eq!(
+            String::from_utf8(kml).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<kml xmlns="http://www.opengis.net/kml/2.2" xmlns:gx="http://www.google.com/kml/ext/2.2">
+<Document>
+<Placemark>
+<name>F-16C</name>
+<gx:Track>
+<altitudeMode>absolute</altitudeMode>
+<when>2024-08-02T10:00:00Z</when>
+<when>2024-08-02T10:00:01.500Z</when>
+<gx:coord>11 22 3</gx:coord>
+<gx:coord>12 22 3</gx:coord>
+</gx:Track>
+</Placemark>
+<Placemark>
+<name>&lt;Tank &amp; Co&gt;</name>
+<gx:Track>
+<altitudeMode>clampToGround</altitudeMode>
+<when>2024-08-02T10:00:00Z</when>
+<gx:coord>14 25 0</gx:coord>
+</gx:Track>
+</Placemark>
+</Document>
+</kml>
+"#
+        );
+
+        let acmi = "FileType=text/acmi/tacview\nFileVersion=2.2\n#0\n1,T=1|2|3\n";
+        assert!(matches!(
+            export(Parser::new(acmi.as_bytes()).unwrap(), Vec::new()),
+            Err(ExportError::MissingReferenceTime)
+        ));
+    }
+}
@@ -0,0 +1,125 @@
+//! Conversions of ACMI recordings into other formats.
+
+#[cfg(feature = "csv")]
+pub mod csv;
+#[cfg(feature = "geojson")]
+pub mod geojson;
+#[cfg(feature = "kml")]
+pub mod kml;
+
+use std::io;
+
+#[cfg(any(feature = "geojson", feature = "kml"))]
+use std::collections::HashMap;
+
+#[cfg(any(feature = "geojson", feature = "kml"))]
+use crate::record::{Property, Record};
+use crate::ParseError;
+#[cfg(any(feature = "geojson", feature = "kml"))]
+use crate::StateTracker;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error("failed to parse recording")]
+    Parse(#[from] ParseError),
+    #[error("error writing output")]
+    Io(#[from] io::Error),
+    #[error("recording has no reference time")]
+    MissingReferenceTime,
+}
+
+/// The positions of a single object, as collected by a [`TrackCollector`].
+#[cfg(any(feature = "geojson", feature = "kml"))]
+struct Track<P> {
+    id: u64,
+    positions: Vec<P>,
+    /// The final properties of the object (except for `T`), captured when it was removed or the
+    /// recording ended.
+    props: Vec<Property>,
+}
+
+/// Collects the track of each object of a recording, in the order the objects appeared. An id
+/// that is reused after its object was removed starts a new track.
+#[cfg(any(feature = "geojson", feature = "kml"))]
+struct TrackCollector<P> {
+    tracker: StateTracker,
+    tracks: Vec<Track<P>>,
+    // index into `tracks` of the track of each object that is still alive
+    alive: HashMap<u64, usize>,
+}
+
+#[cfg(any(feature = "geojson", feature = "kml"))]
+impl<P> TrackCollector<P> {
+    fn new() -> Self {
+        Self {
+            tracker: StateTracker::new(),
+            tracks: Vec::new(),
+            alive: HashMap::new(),
+        }
+    }
+
+    /// Applies the record, and for each update that moves an object with a known longitude and
+    /// latitude, adds the position created by `position` from the frame time and the object's
+    /// absolute `(longitude, latitude, altitude)` to its track.
+    fn apply<E>(
+        &mut self,
+        record: &Record,
+        position: impl FnOnce(f64, f64, f64, Option<f64>) -> Result<P, E>,
+    ) -> Result<(), E> {
+        match record {
+            Record::Remove(id) => {
+                self.finish(*id);
+                self.tracker.apply(record);
+            }
+            Record::Update(update) => {
+                self.tracker.apply(record);
+                if !update.props.iter().any(|p| matches!(p, Property::T(_))) {
+                    return Ok(());
+                }
+                let Some(object) = self.tracker.get(update.id) else {
+                    return Ok(());
+                };
+                let (Some(longitude), Some(latitude)) =
+                    (object.coords.longitude, object.coords.latitude)
+                else {
+                    return Ok(());
+                };
+                let position = position(
+                    self.tracker.time(),
+                    longitude,
+                    latitude,
+                    object.coords.altitude,
+                )?;
+
+                let tracks = &mut self.tracks;
+                let i = *self.alive.entry(update.id).or_insert_with(|| {
+                    tracks.push(Track {
+                        id: update.id,
+                        positions: Vec::new(),
+                        props: Vec::new(),
+                    });
+                    tracks.len() - 1
+                });
+                tracks[i].positions.push(position);
+            }
+            _ => self.tracker.apply(record),
+        }
+        Ok(())
+    }
+
+    /// Captures the final properties of the object, if it has a track.
+    fn finish(&mut self, id: u64) {
+        if let (Some(i), Some(object)) = (self.alive.remove(&id), self.tracker.get(id)) {
+            self.tracks[i].props = object.props.clone();
+        }
+    }
+
+    /// Finishes the tracks of all objects that are still alive and returns all tracks.
+    fn into_tracks(mut self) -> Vec<Track<P>> {
+        let alive = self.alive.keys().copied().collect::<Vec<_>>();
+        for id in alive {
+            self.finish(id);
+        }
+        self.tracks
+    }
+}
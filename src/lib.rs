@@ -1,6 +1,47 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("either the `std` or the `libm` feature must be enabled");
+
+#[cfg(feature = "tokio")]
+pub mod async_parser;
+#[cfg(feature = "std")]
+pub mod bounds;
+#[cfg(feature = "std")]
+pub mod census;
+#[cfg(feature = "std")]
+pub mod dedup;
+mod error;
+#[cfg(any(feature = "csv", feature = "geojson", feature = "kml"))]
+pub mod export;
+mod math;
+#[cfg(feature = "std")]
 pub mod parser;
+#[cfg(feature = "std")]
+pub mod push_parser;
+#[cfg(feature = "std")]
+pub mod realtime;
+#[cfg(feature = "std")]
+pub mod recenter;
 pub mod record;
+#[cfg(feature = "std")]
+pub mod state;
+pub mod units;
+#[cfg(feature = "std")]
 pub mod writer;
 
-pub use parser::{ParseError, Parser};
+#[cfg(feature = "tokio")]
+pub use async_parser::AsyncParser;
+pub use error::ParseError;
+#[cfg(feature = "std")]
+pub use parser::{Parser, ParserCheckpoint};
+#[cfg(feature = "std")]
+pub use push_parser::PushParser;
+#[cfg(feature = "std")]
+pub use realtime::{RealTimeReader, RealTimeWriter};
+#[cfg(feature = "std")]
+pub use state::StateTracker;
+#[cfg(feature = "std")]
 pub use writer::Writer;
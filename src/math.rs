@@ -0,0 +1,87 @@
+//! Floating point functions that are not part of `core`, provided by `std` or, in `no_std`
+//! builds, by `libm`.
+
+#[cfg(feature = "std")]
+pub fn round(x: f64) -> f64 {
+    x.round()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn round(x: f64) -> f64 {
+    libm::round(x)
+}
+
+#[cfg(feature = "std")]
+pub fn powi(x: f64, n: i32) -> f64 {
+    x.powi(n)
+}
+
+#[cfg(not(feature = "std"))]
+pub fn powi(x: f64, n: i32) -> f64 {
+    libm::pow(x, f64::from(n))
+}
+
+#[cfg(feature = "std")]
+pub fn sqrt(x: f64) -> f64 {
+    x.sqrt()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn sqrt(x: f64) -> f64 {
+    libm::sqrt(x)
+}
+
+#[cfg(feature = "std")]
+pub fn sin(x: f64) -> f64 {
+    x.sin()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn sin(x: f64) -> f64 {
+    libm::sin(x)
+}
+
+#[cfg(feature = "std")]
+pub fn cos(x: f64) -> f64 {
+    x.cos()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn cos(x: f64) -> f64 {
+    libm::cos(x)
+}
+
+#[cfg(feature = "std")]
+pub fn asin(x: f64) -> f64 {
+    x.asin()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn asin(x: f64) -> f64 {
+    libm::asin(x)
+}
+
+#[cfg(feature = "std")]
+pub fn rem_euclid(x: f64, y: f64) -> f64 {
+    x.rem_euclid(y)
+}
+
+#[cfg(not(feature = "std"))]
+pub fn rem_euclid(x: f64, y: f64) -> f64 {
+    let r = x % y;
+    if r < 0.0 {
+        r + libm::fabs(y)
+    } else {
+        r
+    }
+}
+
+#[cfg(feature = "std")]
+pub fn hypot(x: f64, y: f64) -> f64 {
+    x.hypot(y)
+}
+
+#[cfg(not(feature = "std"))]
+pub fn hypot(x: f64, y: f64) -> f64 {
+    libm::hypot(x, y)
+}
@@ -1,40 +1,100 @@
-use std::io::{BufReader, Read};
-use std::str::FromStr;
+use std::io::{Cursor, Read, Seek, SeekFrom};
 
 use zip::read::ZipFile;
 use zip::result::ZipError;
+use zip::ZipArchive;
 
-use crate::record::{self, Record};
+pub use crate::error::ParseError;
+use crate::record::{
+    CoordError, Event, EventKind, GlobalProperty, Property, Record, RecordRef, Update,
+};
 
 pub struct Parser<R> {
-    lines: lines::Lines<BufReader<R>>,
+    lines: lines::Lines<input::Input<R>>,
+    minor_version: u8,
+    resolve_references: bool,
+    reference_longitude: Option<f64>,
+    reference_latitude: Option<f64>,
+    lenient: bool,
+    validate_coordinates: bool,
+    monotonic_frames: bool,
+    strict_events: bool,
+    unique_properties: bool,
+    last_frame: Option<f64>,
+    skipped: Vec<ParseError>,
 }
 
+/// The state of a [`Parser`] between two records, to resume parsing later on, see
+/// [`Parser::checkpoint`] and [`Parser::resume`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParserCheckpoint {
+    /// The byte offset of the next record, relative to the start of the input.
+    pub offset: u64,
+    /// The number of lines before the next record.
+    pub line: usize,
+    pub minor_version: u8,
+    pub reference_longitude: Option<f64>,
+    pub reference_latitude: Option<f64>,
+    pub last_frame: Option<f64>,
+}
+
+/// The signature at the start of a zip file, see [`Parser::auto`].
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// A record and its absolute time, see [`Parser::with_absolute_time`].
+#[cfg(feature = "chrono")]
+pub type TimedRecord = (Option<chrono::DateTime<chrono::Utc>>, Record);
+
+/// A file of a zip archive, see [`Parser::from_zip_archive`].
+type ArchiveEntry = (String, Parser<Cursor<Vec<u8>>>);
+
 impl<R> Parser<R> {
     pub fn new(rd: R) -> Result<Self, ParseError>
     where
         R: Read,
     {
-        let mut lines = lines::Lines::new(BufReader::new(rd));
+        let mut lines = lines::Lines::new(input::Input::new(rd)?);
 
-        let file_type = lines.next().ok_or(ParseError::InvalidFileType)??;
-        if file_type != "FileType=text/acmi/tacview"
-            && file_type != "\u{feff}FileType=text/acmi/tacview"
-        {
-            return Err(ParseError::InvalidFileType);
-        }
+        let file_type = loop {
+            let line = lines.next().ok_or(ParseError::InvalidFileType)??;
+            if !is_banner_line(&line) {
+                break line;
+            }
+        };
+        check_file_type(&file_type)?;
 
         let version = lines.next().ok_or(ParseError::InvalidVersion)??;
-        if version.get(..version.len() - 1) != Some("FileVersion=2.")
-            || !version
-                .get(version.len() - 1..)
-                .map(|s| s.chars().all(|c| c.is_ascii_digit()))
-                .unwrap_or(false)
-        {
-            return Err(ParseError::InvalidVersion);
-        }
+        let minor_version = check_version(&version)?;
+
+        Ok(Parser::with_lines(lines, minor_version))
+    }
+
+    /// Creates a parser for input without the `FileType` and `FileVersion` header, e.g. a fragment
+    /// of a recording. The input is parsed as version `2.2`. A byte order mark is still handled.
+    pub fn new_headerless(rd: R) -> Result<Self, ParseError>
+    where
+        R: Read,
+    {
+        let lines = lines::Lines::new(input::Input::new(rd)?);
+        Ok(Parser::with_lines(lines, 2))
+    }
 
-        Ok(Parser { lines })
+    fn with_lines(lines: lines::Lines<input::Input<R>>, minor_version: u8) -> Self {
+        Parser {
+            lines,
+            minor_version,
+            resolve_references: false,
+            reference_longitude: None,
+            reference_latitude: None,
+            lenient: false,
+            validate_coordinates: false,
+            monotonic_frames: false,
+            strict_events: false,
+            unique_properties: false,
+            last_frame: None,
+            skipped: Vec::new(),
+        }
     }
 
     pub fn new_compressed(rd: &mut R) -> Result<Parser<ZipFile<'_>>, ParseError>
@@ -45,6 +105,359 @@ impl<R> Parser<R> {
             .ok_or(ParseError::Zip(ZipError::FileNotFound))?;
         Parser::new(file)
     }
+
+    /// Creates a parser for a gzip compressed ACMI file (`.acmi.gz`).
+    #[cfg(feature = "flate2")]
+    pub fn new_gzip(rd: R) -> Result<Parser<flate2::read::GzDecoder<R>>, ParseError>
+    where
+        R: Read,
+    {
+        Parser::new(flate2::read::GzDecoder::new(rd))
+    }
+
+    /// Creates a parser for either a plain text or a zip compressed ACMI file, depending on
+    /// whether the input starts with the zip file signature. The signature is peeked by reading
+    /// the first bytes and seeking back to where `rd` was before, thus `rd` must support [`Seek`].
+    /// Wrap inputs that cannot seek (e.g. network streams) in a [`Cursor`] after reading them
+    /// into memory.
+    pub fn auto(rd: &mut R) -> Result<Parser<Box<dyn Read + '_>>, ParseError>
+    where
+        R: Read + Seek,
+    {
+        let start = rd.stream_position()?;
+        let mut magic = Vec::with_capacity(ZIP_MAGIC.len());
+        rd.by_ref()
+            .take(ZIP_MAGIC.len() as u64)
+            .read_to_end(&mut magic)?;
+        rd.seek(SeekFrom::Start(start))?;
+
+        if magic == ZIP_MAGIC {
+            let file = zip::read::read_zipfile_from_stream(rd)?
+                .ok_or(ParseError::Zip(ZipError::FileNotFound))?;
+            Parser::new(Box::new(file))
+        } else {
+            Parser::new(Box::new(rd))
+        }
+    }
+
+    /// Yields a parser for each `.acmi` file in the archive, together with the file's name. Other
+    /// files and directories are skipped. Each file is decompressed into memory before it is
+    /// parsed, as the entries of a [`ZipArchive`] can only be read one at a time.
+    pub fn from_zip_archive(
+        mut archive: ZipArchive<R>,
+    ) -> impl Iterator<Item = Result<ArchiveEntry, ParseError>>
+    where
+        R: Read + Seek,
+    {
+        (0..archive.len()).filter_map(move |i| {
+            let file = match archive.by_index(i) {
+                Ok(file) => file,
+                Err(err) => return Some(Err(err.into())),
+            };
+            if file.is_dir() || !file.name().to_ascii_lowercase().ends_with(".acmi") {
+                return None;
+            }
+
+            let name = file.name().to_string();
+            Some(parse_zip_file(file).map(|parser| (name, parser)))
+        })
+    }
+
+    /// Parses the file `name` of the archive. Unlike [`Parser::new_compressed`], this reads the
+    /// archive's central directory and thus works for archives containing multiple files. The
+    /// file is decompressed into memory before it is parsed.
+    pub fn from_zip_member(
+        mut archive: ZipArchive<R>,
+        name: &str,
+    ) -> Result<Parser<Cursor<Vec<u8>>>, ParseError>
+    where
+        R: Read + Seek,
+    {
+        parse_zip_file(archive.by_name(name)?)
+    }
+
+    /// The `(major, minor)` version of the ACMI file.
+    pub fn file_version(&self) -> (u8, u8) {
+        (2, self.minor_version)
+    }
+
+    /// Offset the longitude and latitude of all object coordinates by the most recent
+    /// `ReferenceLongitude` and `ReferenceLatitude` global properties, so that the yielded updates
+    /// contain absolute coordinates. The reference global properties themselves are still yielded.
+    pub fn with_reference_resolution(mut self) -> Self {
+        self.resolve_references = true;
+        self
+    }
+
+    /// Skip lines that fail to parse instead of yielding an error for them. The errors of all
+    /// skipped lines are available via [`Parser::skipped`]. Errors reading the input are still
+    /// yielded. Does not apply to [`Parser::next_ref`], as borrowed records are only parsed lazily.
+    pub fn lenient(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+
+    /// Check that all object coordinates are within their valid ranges and yield an error for
+    /// updates that are not (see [`crate::record::Coords::validate`]). Reference offsets are taken
+    /// into account.
+    pub fn with_coordinate_validation(mut self) -> Self {
+        self.validate_coordinates = true;
+        self
+    }
+
+    /// Yield an error for frames whose time is before the time of the latest valid frame. In
+    /// [`Parser::lenient`] mode, such frames are skipped.
+    pub fn with_monotonic_frames(mut self) -> Self {
+        self.monotonic_frames = true;
+        self
+    }
+
+    /// Yield an error for events of an unknown kind instead of parsing them as
+    /// [`EventKind::Unknown`], e.g. to discover event kinds not supported yet. In
+    /// [`Parser::lenient`] mode, such events are skipped.
+    pub fn with_strict_events(mut self) -> Self {
+        self.strict_events = true;
+        self
+    }
+
+    /// Yield an error for updates that contain a property more than once (e.g. `3,HDG=10,HDG=20`)
+    /// instead of keeping all of them. In [`Parser::lenient`] mode, such updates are skipped.
+    pub fn with_unique_properties(mut self) -> Self {
+        self.unique_properties = true;
+        self
+    }
+
+    /// Captures the state of the parser after the most recently yielded record. Returns `None`
+    /// for UTF-16 input, which is transcoded in chunks and thus has no byte offset per record.
+    pub fn checkpoint(&self) -> Option<ParserCheckpoint> {
+        if self.lines.get_ref().is_utf16() {
+            return None;
+        }
+        Some(ParserCheckpoint {
+            offset: self.bytes_read(),
+            line: self.lines.line(),
+            minor_version: self.minor_version,
+            reference_longitude: self.reference_longitude,
+            reference_latitude: self.reference_latitude,
+            last_frame: self.last_frame,
+        })
+    }
+
+    /// Creates a parser that continues where the checkpoint was taken, by seeking `rd` to the
+    /// checkpoint's offset. `rd` must be the same input the checkpoint was taken of, with offsets
+    /// relative to its start (i.e. it was at position `0` when the original parser was created).
+    /// The header is not parsed again. Options like [`Parser::with_reference_resolution`] are not
+    /// part of the checkpoint and must be set again.
+    pub fn resume(mut rd: R, checkpoint: ParserCheckpoint) -> Result<Self, ParseError>
+    where
+        R: Read + Seek,
+    {
+        rd.seek(SeekFrom::Start(checkpoint.offset))?;
+        let mut lines = lines::Lines::new(input::Input::new_at(rd, checkpoint.offset));
+        lines.set_line(checkpoint.line);
+
+        let mut parser = Parser::with_lines(lines, checkpoint.minor_version);
+        parser.reference_longitude = checkpoint.reference_longitude;
+        parser.reference_latitude = checkpoint.reference_latitude;
+        parser.last_frame = checkpoint.last_frame;
+        Ok(parser)
+    }
+
+    /// The number of bytes parsed so far, e.g. to show the progress of parsing a file by dividing
+    /// it by the file size. For compressed files, this is the number of uncompressed bytes.
+    pub fn bytes_read(&self) -> u64 {
+        self.lines.get_ref().bytes_read()
+    }
+
+    /// Returns the underlying reader. The parser reads its input in chunks, so input that has
+    /// already been read from the reader but not been parsed yet (i.e. the lines after the most
+    /// recently yielded record, up to the buffer size of 8 KiB) is lost.
+    pub fn into_inner(self) -> R {
+        self.lines.into_inner().into_inner()
+    }
+
+    /// The errors of all lines skipped so far in [`Parser::lenient`] mode.
+    pub fn skipped(&self) -> &[ParseError] {
+        &self.skipped
+    }
+
+    /// Yield each record together with its absolute UTC time, i.e. the most recent
+    /// `ReferenceTime` global property plus the time of the current frame (`0` before the first
+    /// frame). The time is `None` as long as no (well-formed) `ReferenceTime` has been read.
+    #[cfg(feature = "chrono")]
+    pub fn with_absolute_time(self) -> impl Iterator<Item = Result<TimedRecord, ParseError>>
+    where
+        R: Read,
+    {
+        let mut reference_time = None;
+        let mut frame = 0.0;
+        self.map(move |record| {
+            let record = record?;
+            match &record {
+                Record::GlobalProperty(p @ GlobalProperty::ReferenceTime(_)) => {
+                    reference_time = p.reference_time();
+                }
+                Record::Frame(time) => frame = *time,
+                _ => {}
+            }
+            let offset = chrono::TimeDelta::microseconds((frame * 1_000_000.0).round() as i64);
+            let time = reference_time.and_then(|t| t.checked_add_signed(offset));
+            Ok((time, record))
+        })
+    }
+
+    /// Only yield object updates (and errors).
+    pub fn updates(self) -> impl Iterator<Item = Result<Update, ParseError>>
+    where
+        R: Read,
+    {
+        self.filter_map(|r| match r {
+            Ok(Record::Update(update)) => Some(Ok(update)),
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+    }
+
+    /// Only yield events (and errors).
+    pub fn events(self) -> impl Iterator<Item = Result<Event, ParseError>>
+    where
+        R: Read,
+    {
+        self.filter_map(|r| match r {
+            Ok(Record::Event(event)) => Some(Ok(event)),
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+    }
+
+    /// Only yield frame times (and errors).
+    pub fn frames(self) -> impl Iterator<Item = Result<f64, ParseError>>
+    where
+        R: Read,
+    {
+        self.filter_map(|r| match r {
+            Ok(Record::Frame(time)) => Some(Ok(time)),
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+    }
+
+    /// Collects all remaining records, stopping at the first error.
+    pub fn into_records(self) -> Result<Vec<Record>, ParseError>
+    where
+        R: Read,
+    {
+        self.collect()
+    }
+
+    /// Like [`Parser::into_records`], but reserves the capacity for the records up front, based
+    /// on the remaining length of the input and an estimate of the average record size. The
+    /// length is determined by seeking to the end of the input and back.
+    pub fn into_records_presized(mut self) -> Result<Vec<Record>, ParseError>
+    where
+        R: Read + Seek,
+    {
+        /// Rough estimate of the average record length (in bytes) of typical recordings.
+        const AVERAGE_RECORD_LEN: u64 = 32;
+
+        let rd = self.lines.get_mut().get_mut();
+        let position = rd.stream_position()?;
+        let len = rd.seek(SeekFrom::End(0))?;
+        rd.seek(SeekFrom::Start(position))?;
+
+        let mut records =
+            Vec::with_capacity((len.saturating_sub(position) / AVERAGE_RECORD_LEN) as usize);
+        for record in self {
+            records.push(record?);
+        }
+        Ok(records)
+    }
+
+    /// Reads the next record without copying any of its text values. The returned record borrows
+    /// from a line buffer that is reused for the next call. References are not resolved for
+    /// borrowed records, even if [`Parser::with_reference_resolution`] is enabled.
+    pub fn next_ref(&mut self) -> Option<Result<RecordRef<'_>, ParseError>>
+    where
+        R: Read,
+    {
+        let line = loop {
+            let line = self.lines.line() + 1;
+            match self.lines.read_line()? {
+                Ok("") => return None,
+                Ok(l) if l.starts_with("//") => continue,
+                Ok(_) => break line,
+                Err(err) => return Some(Err(ParseError::Io(err))),
+            }
+        };
+
+        RecordRef::parse(self.lines.current())
+            .map_err(|err| ParseError::At {
+                line,
+                source: Box::new(err),
+            })
+            .transpose()
+    }
+
+    fn check_frame_order(&mut self, record: &Record) -> Result<(), ParseError> {
+        let Record::Frame(time) = *record else {
+            return Ok(());
+        };
+        if let Some(previous) = self.last_frame {
+            if time < previous {
+                return Err(ParseError::NonMonotonicFrame { previous, time });
+            }
+        }
+        self.last_frame = Some(time);
+        Ok(())
+    }
+
+    fn validate_coordinates(&self, record: &Record) -> Result<(), CoordError> {
+        let Record::Update(update) = record else {
+            return Ok(());
+        };
+        let (reference_latitude, reference_longitude) = if self.resolve_references {
+            (0.0, 0.0)
+        } else {
+            (
+                self.reference_latitude.unwrap_or_default(),
+                self.reference_longitude.unwrap_or_default(),
+            )
+        };
+        for prop in &update.props {
+            if let Property::T(coords) = prop {
+                coords.validate(reference_latitude, reference_longitude)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_reference(&mut self, record: &mut Record) {
+        match record {
+            Record::GlobalProperty(GlobalProperty::ReferenceLongitude(v)) => {
+                self.reference_longitude = Some(*v);
+            }
+            Record::GlobalProperty(GlobalProperty::ReferenceLatitude(v)) => {
+                self.reference_latitude = Some(*v);
+            }
+            Record::Update(update) if self.resolve_references => {
+                for prop in &mut update.props {
+                    if let Property::T(coords) = prop {
+                        if let (Some(longitude), Some(reference)) =
+                            (&mut coords.longitude, self.reference_longitude)
+                        {
+                            *longitude += reference;
+                        }
+                        if let (Some(latitude), Some(reference)) =
+                            (&mut coords.latitude, self.reference_latitude)
+                        {
+                            *latitude += reference;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
 }
 
 impl<R> Iterator for Parser<R>
@@ -55,127 +468,479 @@ where
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            let next = self
-                .lines
-                .next()
-                .filter(|r| r.as_ref().map(|l| !l.is_empty()).unwrap_or(true))?
-                .map_err(ParseError::Io)
-                .and_then(parse_line)
-                .transpose();
-            if next.is_some() {
-                return next;
+            let line = self.lines.line() + 1;
+            let next = match self.lines.read_line()? {
+                Ok("") => return None,
+                Ok(l) => l,
+                Err(err) => return Some(Err(ParseError::Io(err))),
+            };
+
+            let record = parse_line(next).and_then(|record| {
+                let Some(mut record) = record else {
+                    return Ok(None);
+                };
+                self.resolve_reference(&mut record);
+                if self.monotonic_frames {
+                    self.check_frame_order(&record)?;
+                }
+                if self.validate_coordinates {
+                    self.validate_coordinates(&record)?;
+                }
+                if self.strict_events {
+                    if let Record::Event(Event {
+                        kind: EventKind::Unknown(name),
+                        ..
+                    }) = record
+                    {
+                        return Err(ParseError::UnknownEvent(name));
+                    }
+                }
+                if self.unique_properties {
+                    check_unique_properties(&record)?;
+                }
+                Ok(Some(record))
+            });
+
+            match record {
+                Ok(Some(record)) => return Some(Ok(record)),
+                Ok(None) => {}
+                Err(err) => {
+                    let err = ParseError::At {
+                        line,
+                        source: Box::new(err),
+                    };
+                    if self.lenient {
+                        self.skipped.push(err);
+                    } else {
+                        return Some(Err(err));
+                    }
+                }
             }
         }
     }
 }
 
-fn parse_line(line: String) -> Result<Option<Record>, ParseError> {
-    let mut chars = line.chars();
-    match chars.next().ok_or(ParseError::Eol)? {
-        '-' => {
-            let id = u64::from_str_radix(&line[1..], 16)?;
-            Ok(Some(Record::Remove(id)))
+/// Decompresses a zip archive entry into memory and parses it.
+fn parse_zip_file(mut file: ZipFile<'_>) -> Result<Parser<Cursor<Vec<u8>>>, ParseError> {
+    let mut buf = Vec::with_capacity(file.size() as usize);
+    file.read_to_end(&mut buf)?;
+    Parser::new(Cursor::new(buf))
+}
+
+/// Whether the line is a comment or blank line before the `FileType` header, as prepended by some
+/// exporters.
+pub(crate) fn is_banner_line(line: &str) -> bool {
+    let line = line.strip_prefix('\u{feff}').unwrap_or(line).trim();
+    line.is_empty() || line.starts_with("//")
+}
+
+fn check_unique_properties(record: &Record) -> Result<(), ParseError> {
+    let Record::Update(update) = record else {
+        return Ok(());
+    };
+    for (i, prop) in update.props.iter().enumerate() {
+        if update.props[..i].iter().any(|p| p.same_key(prop)) {
+            return Err(ParseError::DuplicateProperty(prop.to_kv().0));
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn check_file_type(line: &str) -> Result<(), ParseError> {
+    if line != "FileType=text/acmi/tacview" && line != "\u{feff}FileType=text/acmi/tacview" {
+        return Err(ParseError::InvalidFileType);
+    }
+    Ok(())
+}
+
+/// Checks that the version is a `2.x` version and returns its minor version.
+pub(crate) fn check_version(line: &str) -> Result<u8, ParseError> {
+    line.strip_prefix("FileVersion=2.")
+        .filter(|minor| minor.len() == 1)
+        .and_then(|minor| minor.parse().ok())
+        .ok_or(ParseError::InvalidVersion)
+}
+
+pub(crate) fn parse_line(line: &str) -> Result<Option<Record>, ParseError> {
+    Record::parse(line)
+}
+
+pub(crate) mod lines {
+    use std::io::{self, BufRead};
+    use std::mem;
+
+    /// Removes the line break (`\n`, `\r\n` or a bare `\r`) from the end of `buf`. Returns
+    /// `false` if the line break is escaped, in which case only the escaping backslash is removed,
+    /// as the line continues on the next one. A line break is only escaped by an odd number of
+    /// backslashes, as an even number are escaped backslashes (e.g. a value ending with a Windows
+    /// path separator).
+    pub fn finish_line(buf: &mut String) -> bool {
+        let line_break = if buf.ends_with("\r\n") {
+            2
+        } else if buf.ends_with(['\n', '\r']) {
+            1
+        } else {
+            return true;
+        };
+        let content = &buf[..buf.len() - line_break];
+        let backslashes = content.len() - content.trim_end_matches('\\').len();
+        if backslashes % 2 == 1 {
+            buf.remove(content.len() - 1);
+            return false;
         }
-        '#' => {
-            let id = f64::from_str(&line[1..])?;
-            Ok(Some(Record::Frame(id)))
+        buf.truncate(content.len());
+        true
+    }
+
+    /// Removes a trailing carriage return from the last line of the input, which is not terminated
+    /// by a line break.
+    pub fn finish_last_line(buf: &mut String) {
+        if buf.ends_with('\r') {
+            buf.pop();
         }
-        '/' if chars.next() == Some('/') => Ok(None),
-        _ => {
-            let (id, rest) = line.split_once(',').ok_or(ParseError::Eol)?;
+    }
+
+    /// Splits input that is read in chunks into physical lines, each ending with a line break
+    /// (`\n`, `\r\n` or a bare `\r`).
+    #[derive(Debug, Default)]
+    pub struct LineSplitter {
+        /// The last line ended with a `\r` at the end of a chunk, so whether it is followed by a
+        /// `\n` is only known once the next chunk is read.
+        carriage_return: bool,
+    }
 
-            Ok(Some(if id == "0" {
-                let (name, value) = rest
-                    .split_once('=')
-                    .ok_or(ParseError::MissingDelimiter('='))?;
-                if name == "Event" {
-                    Record::Event(record::Event::from_str(value)?)
-                } else {
-                    Record::GlobalProperty(record::GlobalProperty::from_str(rest)?)
+    impl LineSplitter {
+        /// Appends the bytes of `chunk` up to and including the next line break to `buf`. Returns
+        /// the number of bytes consumed from `chunk`, and whether a line break was appended.
+        ///
+        /// If the previous line ended with a `\r` at the end of its chunk, this only consumes the
+        /// `\n` completing a `\r\n` line break (if any). The `\n` is appended to `buf`, unless the
+        /// previous line has already been taken from it.
+        pub fn split(&mut self, chunk: &[u8], buf: &mut Vec<u8>) -> (usize, bool) {
+            if mem::take(&mut self.carriage_return) {
+                if chunk.first() != Some(&b'\n') {
+                    return (0, false);
                 }
-            } else {
-                Record::Update(record::Update::from_str(&line)?)
-            }))
+                if !buf.is_empty() {
+                    buf.push(b'\n');
+                }
+                return (1, false);
+            }
+
+            let Some(i) = chunk.iter().position(|b| matches!(b, b'\n' | b'\r')) else {
+                buf.extend_from_slice(chunk);
+                return (chunk.len(), false);
+            };
+            let mut end = i + 1;
+            if chunk[i] == b'\r' {
+                match chunk.get(end) {
+                    Some(b'\n') => end += 1,
+                    Some(_) => {}
+                    None => self.carriage_return = true,
+                }
+            }
+            buf.extend_from_slice(&chunk[..end]);
+            (end, true)
+        }
+
+        /// Whether the last line ended with a `\r` that might still be followed by a `\n`.
+        pub fn is_pending(&self) -> bool {
+            self.carriage_return
         }
     }
-}
 
-mod lines {
-    use std::io::BufRead;
+    /// Splits `text` at line breaks like [`str::lines`], but also at bare `\r`s.
+    pub fn split_lines(text: &str) -> impl Iterator<Item = &str> {
+        let mut rest = Some(text).filter(|text| !text.is_empty());
+        std::iter::from_fn(move || {
+            let text = rest?;
+            let Some(i) = text.find(['\n', '\r']) else {
+                rest = None;
+                return Some(text);
+            };
+            let line_break = if text[i..].starts_with("\r\n") { 2 } else { 1 };
+            rest = Some(&text[i + line_break..]).filter(|rest| !rest.is_empty());
+            Some(&text[..i])
+        })
+    }
 
     /// An iterator over the non-escaped lines of an instance of `BufRead`.
     #[derive(Debug)]
     pub struct Lines<B> {
         buf: B,
+        line: usize,
+        current: String,
+        splitter: LineSplitter,
     }
 
     impl<B> Lines<B> {
         pub fn new(buf: B) -> Self {
-            Self { buf }
+            Self {
+                buf,
+                line: 0,
+                current: String::new(),
+                splitter: LineSplitter::default(),
+            }
         }
-    }
 
-    impl<B: BufRead> Iterator for Lines<B> {
-        type Item = std::io::Result<String>;
+        /// The number of physical lines read so far.
+        pub fn line(&self) -> usize {
+            self.line
+        }
 
-        fn next(&mut self) -> Option<Self::Item> {
-            let mut buf = String::new();
+        pub fn set_line(&mut self, line: usize) {
+            self.line = line;
+        }
+
+        /// The line most recently read by [`Lines::read_line`].
+        pub fn current(&self) -> &str {
+            &self.current
+        }
+
+        pub fn get_ref(&self) -> &B {
+            &self.buf
+        }
+
+        pub fn get_mut(&mut self) -> &mut B {
+            &mut self.buf
+        }
+
+        pub fn into_inner(self) -> B {
+            self.buf
+        }
+    }
+
+    impl<B: BufRead> Lines<B> {
+        /// Reads the next line into a buffer that is reused across calls.
+        pub fn read_line(&mut self) -> Option<std::io::Result<&str>> {
+            self.current.clear();
             loop {
-                match self.buf.read_line(&mut buf) {
+                match read_until_line_break(&mut self.buf, &mut self.splitter, &mut self.current) {
                     Ok(0) => {
-                        if buf.is_empty() {
+                        if self.current.is_empty() {
                             return None;
                         } else {
-                            return Some(Ok(buf));
+                            finish_last_line(&mut self.current);
+                            return Some(Ok(&self.current));
                         }
                     }
                     Ok(_n) => {
-                        if buf.ends_with("\\\n") {
-                            buf.remove(buf.len() - 2);
-                            continue;
-                        }
-                        if buf.ends_with("\\\r\n") {
-                            buf.remove(buf.len() - 3);
-                            continue;
+                        self.line += 1;
+                        if finish_line(&mut self.current) {
+                            return Some(Ok(&self.current));
                         }
-                        if buf.ends_with('\n') {
-                            buf.pop();
-                            if buf.ends_with('\r') {
-                                buf.pop();
-                            }
-                        }
-                        return Some(Ok(buf));
                     }
                     Err(e) => return Some(Err(e)),
                 }
             }
         }
     }
+
+    /// Like [`BufRead::read_line`], but also ends lines at a bare `\r` (classic Mac OS line
+    /// endings). Appends the line including its line break to `out` and returns the number of
+    /// bytes read, `0` at the end of the input.
+    fn read_until_line_break(
+        rd: &mut impl BufRead,
+        splitter: &mut LineSplitter,
+        out: &mut String,
+    ) -> io::Result<usize> {
+        let mut bytes = mem::take(out).into_bytes();
+        let start = bytes.len();
+        let mut line_break = false;
+        let result = loop {
+            let available = match rd.fill_buf() {
+                Ok(available) => available,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => break Err(err),
+            };
+            if available.is_empty() {
+                break Ok(());
+            }
+            let (n, complete) = splitter.split(available, &mut bytes);
+            rd.consume(n);
+            line_break |= complete;
+            // the `\n` of a `\r\n` line break might not have been read yet, it is consumed right
+            // away so that the reader is positioned at the start of the next line
+            if line_break && !splitter.is_pending() {
+                break Ok(());
+            }
+        };
+
+        let read = bytes.len() - start;
+        *out = String::from_utf8(bytes).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )
+        })?;
+        result.map(|()| read)
+    }
+
+    impl<B: BufRead> Iterator for Lines<B> {
+        type Item = std::io::Result<String>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.read_line().map(|line| line.map(String::from))
+        }
+    }
 }
 
-// TODO: line and position information for certain errors?
-#[derive(Debug, thiserror::Error)]
-pub enum ParseError {
-    #[error("input is not a ACMI file")]
-    InvalidFileType,
-    #[error("invalid version, expected ACMI v2.x")]
-    InvalidVersion,
-    #[error("error reading input")]
-    Io(#[from] std::io::Error),
-    #[error("unexpected end of line")]
-    Eol,
-    #[error("object id is not a u64")]
-    InvalidId(#[from] std::num::ParseIntError),
-    #[error("expected numeric")]
-    InvalidNumeric(#[from] std::num::ParseFloatError),
-    #[error("could not find expected delimiter `{0}`")]
-    MissingDelimiter(char),
-    #[error("failed to parse event")]
-    InvalidEvent,
-    #[error("encountered invalid coordinate format")]
-    InvalidCoordinateFormat,
-    #[error("error reading zip compressed input")]
-    Zip(#[from] zip::result::ZipError),
+mod input {
+    use std::io::{self, BufRead, BufReader, Read};
+
+    /// Strips a leading byte order mark and transcodes UTF-16 input to UTF-8.
+    pub struct Input<R> {
+        rd: BufReader<R>,
+        utf16: Option<Utf16>,
+        /// The number of bytes consumed from `rd`.
+        consumed: u64,
+    }
+
+    struct Utf16 {
+        big_endian: bool,
+        /// The first byte of a code unit split across reads.
+        odd_byte: Option<u8>,
+        /// A high surrogate whose low surrogate was not read yet.
+        high_surrogate: Option<u16>,
+        out: String,
+        pos: usize,
+    }
+
+    impl<R> Input<R> {
+        /// The number of bytes of the underlying reader consumed so far, including the byte order
+        /// mark. For UTF-16 input, all bytes of a transcoded chunk count as consumed right away.
+        pub fn bytes_read(&self) -> u64 {
+            self.consumed
+        }
+
+        pub fn is_utf16(&self) -> bool {
+            self.utf16.is_some()
+        }
+
+        /// The underlying reader. Reading from it directly skips any buffered input.
+        pub fn get_mut(&mut self) -> &mut R {
+            self.rd.get_mut()
+        }
+
+        /// The underlying reader. Any buffered input is lost.
+        pub fn into_inner(self) -> R {
+            self.rd.into_inner()
+        }
+    }
+
+    impl<R: Read> Input<R> {
+        /// Wraps UTF-8 input that has already been read up to `consumed` bytes, without checking
+        /// for a byte order mark.
+        pub fn new_at(rd: R, consumed: u64) -> Self {
+            Self {
+                rd: BufReader::new(rd),
+                utf16: None,
+                consumed,
+            }
+        }
+
+        pub fn new(rd: R) -> io::Result<Self> {
+            let mut rd = BufReader::new(rd);
+            let (bom_len, big_endian) = match rd.fill_buf()? {
+                [0xEF, 0xBB, 0xBF, ..] => (3, None),
+                [0xFF, 0xFE, ..] => (2, Some(false)),
+                [0xFE, 0xFF, ..] => (2, Some(true)),
+                _ => (0, None),
+            };
+            rd.consume(bom_len);
+
+            Ok(Self {
+                rd,
+                consumed: bom_len as u64,
+                utf16: big_endian.map(|big_endian| Utf16 {
+                    big_endian,
+                    odd_byte: None,
+                    high_surrogate: None,
+                    out: String::new(),
+                    pos: 0,
+                }),
+            })
+        }
+    }
+
+    impl<R: Read> Read for Input<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let available = self.fill_buf()?;
+            let len = available.len().min(buf.len());
+            buf[..len].copy_from_slice(&available[..len]);
+            self.consume(len);
+            Ok(len)
+        }
+    }
+
+    impl<R: Read> BufRead for Input<R> {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            let Some(utf16) = &mut self.utf16 else {
+                return self.rd.fill_buf();
+            };
+
+            while utf16.pos == utf16.out.len() {
+                utf16.out.clear();
+                utf16.pos = 0;
+
+                let available = self.rd.fill_buf()?;
+                if available.is_empty() {
+                    if utf16.odd_byte.is_some() || utf16.high_surrogate.is_some() {
+                        return Err(invalid_utf16());
+                    }
+                    break;
+                }
+                let len = available.len();
+
+                let mut bytes = utf16
+                    .odd_byte
+                    .take()
+                    .into_iter()
+                    .chain(available.iter().copied());
+                let mut units = Vec::with_capacity(len / 2 + 2);
+                units.extend(utf16.high_surrogate.take());
+                while let Some(a) = bytes.next() {
+                    let Some(b) = bytes.next() else {
+                        utf16.odd_byte = Some(a);
+                        break;
+                    };
+                    units.push(if utf16.big_endian {
+                        u16::from_be_bytes([a, b])
+                    } else {
+                        u16::from_le_bytes([a, b])
+                    });
+                }
+                if matches!(units.last(), Some(0xD800..=0xDBFF)) {
+                    utf16.high_surrogate = units.pop();
+                }
+
+                for ch in char::decode_utf16(units) {
+                    utf16.out.push(ch.map_err(|_| invalid_utf16())?);
+                }
+                self.rd.consume(len);
+                self.consumed += len as u64;
+            }
+
+            Ok(&utf16.out.as_bytes()[utf16.pos..])
+        }
+
+        fn consume(&mut self, amt: usize) {
+            match &mut self.utf16 {
+                Some(utf16) => utf16.pos += amt,
+                None => {
+                    self.rd.consume(amt);
+                    self.consumed += amt as u64;
+                }
+            }
+        }
+    }
+
+    fn invalid_utf16() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "stream did not contain valid UTF-16",
+        )
+    }
 }
 
 #[test]
@@ -192,8 +957,746 @@ FileVersion=2.2
     assert_eq!(
         records,
         vec![
-            Record::GlobalProperty(record::GlobalProperty::Comments("1\n2\n\n3".to_string())),
-            Record::GlobalProperty(record::GlobalProperty::Title("Test".to_string()))
+            Record::GlobalProperty(crate::record::GlobalProperty::Comments(
+                "1\n2\n\n3".to_string()
+            )),
+            Record::GlobalProperty(crate::record::GlobalProperty::Title("Test".to_string()))
         ]
     );
 }
+
+#[test]
+fn test_file_version() {
+    let p = Parser::new("FileType=text/acmi/tacview\nFileVersion=2.2\n".as_bytes()).unwrap();
+    assert_eq!(p.file_version(), (2, 2));
+
+    let p = Parser::new("FileType=text/acmi/tacview\nFileVersion=2.1\n".as_bytes()).unwrap();
+    assert_eq!(p.file_version(), (2, 1));
+
+    assert!(matches!(
+        Parser::new("FileType=text/acmi/tacview\nFileVersion=3.0\n".as_bytes()),
+        Err(ParseError::InvalidVersion)
+    ));
+}
+
+#[test]
+#[cfg(feature = "chrono")]
+fn test_absolute_time() {
+    use chrono::{TimeZone, Utc};
+
+    let acmi = "FileType=text/acmi/tacview\nFileVersion=2.2\n0,Title=Test\n\
+                0,ReferenceTime=2024-08-02T10:00:00Z\n#0\n#1.25\n1,T=1|2|3\n";
+    let times = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .with_absolute_time()
+        .map(|r| r.unwrap().0)
+        .collect::<Vec<_>>();
+    let reference = Utc.with_ymd_and_hms(2024, 8, 2, 10, 0, 0).unwrap();
+    let later = reference + chrono::TimeDelta::milliseconds(1250);
+    assert_eq!(
+        times,
+        vec![
+            None,
+            Some(reference),
+            Some(reference),
+            Some(later),
+            Some(later)
+        ]
+    );
+}
+
+#[test]
+fn test_into_inner() {
+    let acmi = "FileType=text/acmi/tacview\nFileVersion=2.2\n#1\n";
+    let mut rd = Cursor::new(acmi.as_bytes());
+    let mut p = Parser::new(&mut rd).unwrap();
+    assert_eq!(p.next().unwrap().unwrap(), Record::Frame(1.0));
+    p.into_inner();
+    assert_eq!(rd.position(), acmi.len() as u64);
+}
+
+#[test]
+fn test_error_context() {
+    let err = |line: &str| parse_line(line).unwrap_err().to_string();
+    assert_eq!(err("3f02,HDG=abc"), "HDG `abc` is not numeric");
+    assert_eq!(err("3f02,FuelWeight2=x"), "FuelWeight `x` is not numeric");
+    assert_eq!(err("1,T=1|a|3"), "latitude `a` is not numeric");
+    assert_eq!(err("1,Parent=xyz"), "Parent `xyz` is not a valid integer");
+    assert_eq!(
+        err("xyz,Name=F-16C"),
+        "object id `xyz` is not a valid integer"
+    );
+    assert_eq!(err("#1a"), "frame time `1a` is not numeric");
+    assert_eq!(
+        err("1,Disabled=0.5"),
+        "Disabled `0.5` is not a valid integer"
+    );
+    assert_eq!(err("1,Visible=NaN"), "Visible `NaN` is not a valid integer");
+    assert!(matches!(
+        parse_line("1,Disabled=-1,Visible=0"),
+        Ok(Some(Record::Update(update)))
+            if update.props == [Property::Disabled(true), Property::Visible(false)]
+    ));
+    assert!(matches!(
+        parse_line("1,HDG=abc"),
+        Err(ParseError::InvalidNumeric { field: "HDG", value, .. }) if value == "abc"
+    ));
+}
+
+#[test]
+fn test_carriage_return_line_endings() {
+    let plain = Parser::new(&include_bytes!("../tests/fixtures/track.txt.acmi")[..])
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let cr = Parser::new(&include_bytes!("../tests/fixtures/cr.txt.acmi")[..])
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(cr, plain);
+
+    // mixed line endings, and escaped bare carriage returns
+    let acmi = "FileType=text/acmi/tacview\rFileVersion=2.2\r\n0,Comments=a\\\rb\n#1\r#2";
+    let records = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        records,
+        vec![
+            Record::GlobalProperty(GlobalProperty::Comments("a\rb".to_string())),
+            Record::Frame(1.0),
+            Record::Frame(2.0),
+        ]
+    );
+
+    // bare carriage returns in values are escaped when written
+    let record = Record::GlobalProperty(GlobalProperty::Comments("a\rb".to_string()));
+    let mut wr = crate::Writer::new(Vec::new()).unwrap();
+    wr.write(record.clone()).unwrap();
+    let acmi = wr.into_inner();
+    let parsed = Parser::new(&acmi[..]).unwrap().next().unwrap().unwrap();
+    assert_eq!(parsed, record);
+    let mut push = crate::PushParser::new();
+    let parsed = push.feed(&acmi).next().unwrap().unwrap();
+    assert_eq!(parsed, record);
+}
+
+#[test]
+fn test_banner() {
+    let plain = Parser::new(&include_bytes!("../tests/fixtures/track.txt.acmi")[..])
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let banner = Parser::new(&include_bytes!("../tests/fixtures/banner.txt.acmi")[..])
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(banner, plain);
+
+    assert!(matches!(
+        Parser::new("// only a comment\n\n".as_bytes()),
+        Err(ParseError::InvalidFileType)
+    ));
+}
+
+#[test]
+fn test_headerless() {
+    let acmi = "\u{feff}#1\n1,T=1|2|3\n";
+    let mut p = Parser::new_headerless(acmi.as_bytes()).unwrap();
+    assert_eq!(p.file_version(), (2, 2));
+    assert_eq!(p.next().unwrap().unwrap(), Record::Frame(1.0));
+    assert!(matches!(p.next(), Some(Ok(Record::Update(_)))));
+    assert!(p.next().is_none());
+
+    assert!(matches!(
+        Parser::new(acmi.as_bytes()),
+        Err(ParseError::InvalidFileType)
+    ));
+}
+
+#[test]
+fn test_unterminated_last_line() {
+    let acmi = "FileType=text/acmi/tacview\r\nFileVersion=2.2\r\n0,Title=Test\r";
+    let records = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        records,
+        vec![Record::GlobalProperty(GlobalProperty::Title(
+            "Test".to_string()
+        ))]
+    );
+
+    // the trailing `\r` is a line break, so the last line is complete without calling `finish`
+    let mut p = crate::PushParser::new();
+    assert_eq!(
+        p.feed(acmi.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        records
+    );
+    assert_eq!(p.finish().count(), 0);
+}
+
+#[test]
+fn test_trailing_backslash() {
+    let acmi = "FileType=text/acmi/tacview\nFileVersion=2.2\n\
+                1,Shape=C:\\\\Data\\\\\n\
+                0,Comments=C:\\\\Data\\\\\\\nmore\r\n\
+                #1\n";
+    let records = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        records,
+        vec![
+            Record::Update(Update {
+                id: 1,
+                props: vec![Property::Shape("C:\\Data\\".to_string())]
+            }),
+            Record::GlobalProperty(GlobalProperty::Comments("C:\\Data\\\nmore".to_string())),
+            Record::Frame(1.0),
+        ]
+    );
+
+    let mut wr = crate::Writer::new(Vec::new()).unwrap();
+    wr.write_all(records.clone()).unwrap();
+    let written = wr.into_inner();
+    assert_eq!(
+        Parser::new(written.as_slice())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        records
+    );
+}
+
+#[test]
+fn test_error_line_number() {
+    let acmi = r#"FileType=text/acmi/tacview
+FileVersion=2.2
+0,Comments=1\
+2
+#1
+1,T=1|2
+#2"#;
+    let p = Parser::new(acmi.as_bytes()).unwrap();
+    let err = p.collect::<Result<Vec<_>, _>>().unwrap_err();
+    match err {
+        ParseError::At { line, source } => {
+            assert_eq!(line, 6);
+            assert!(matches!(*source, ParseError::InvalidCoordinateFormat));
+        }
+        err => panic!("unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn test_escaped_values_roundtrip() {
+    let records = vec![
+        Record::GlobalProperty(crate::record::GlobalProperty::Comments(
+            "first, line\nsecond\\line".to_string(),
+        )),
+        Record::Update(crate::record::Update {
+            id: 1,
+            props: vec![
+                crate::record::Property::Label("foo,bar".to_string()),
+                crate::record::Property::Name("multi\nline".to_string()),
+                crate::record::Property::Color(crate::record::Color::Unknown("a,b".to_string())),
+                crate::record::Property::Type(
+                    [crate::record::Tag::Unknown("X,Y".to_string())]
+                        .into_iter()
+                        .collect(),
+                ),
+            ],
+        }),
+    ];
+
+    let mut wr = crate::Writer::new(Vec::new()).unwrap();
+    for record in &records {
+        wr.write(record.clone()).unwrap();
+    }
+    let written = wr.into_inner();
+
+    let p = Parser::new(written.as_slice()).unwrap();
+    let parsed = p.collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(parsed, records);
+
+    let mut wr = crate::Writer::new(Vec::new()).unwrap();
+    for record in parsed {
+        wr.write(record).unwrap();
+    }
+    assert_eq!(wr.into_inner(), written);
+}
+
+#[test]
+fn test_reference_resolution() {
+    let acmi = r#"FileType=text/acmi/tacview
+FileVersion=2.2
+1,T=1|2|3
+0,ReferenceLongitude=10
+0,ReferenceLatitude=20
+#0
+2,T=1|2|3
+3,T=||4"#;
+    let p = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .with_reference_resolution();
+    let coords = p
+        .updates()
+        .map(|u| match u.unwrap().props.remove(0) {
+            Property::T(coords) => coords,
+            p => panic!("unexpected property: {p:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        coords,
+        vec![
+            crate::record::Coords::default().position(2.0, 1.0, 3.0),
+            crate::record::Coords::default().position(22.0, 11.0, 3.0),
+            crate::record::Coords {
+                altitude: Some(4.0),
+                ..Default::default()
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_reference_change() {
+    let acmi = "FileType=text/acmi/tacview\nFileVersion=2.2\n\
+                0,ReferenceLongitude=10\n0,ReferenceLatitude=20\n#0\n1,T=1|2|3\n\
+                #1\n0,ReferenceLongitude=30\n1,T=1|2|3\n0,ReferenceLatitude=40\n1,T=1|2|3\n";
+    let coords = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .with_reference_resolution()
+        .updates()
+        .map(|u| match u.unwrap().props.remove(0) {
+            Property::T(coords) => (coords.longitude, coords.latitude),
+            p => panic!("unexpected property: {p:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        coords,
+        vec![
+            (Some(11.0), Some(22.0)),
+            (Some(31.0), Some(22.0)),
+            (Some(31.0), Some(42.0)),
+        ]
+    );
+}
+
+#[test]
+fn test_bytes_read() {
+    let acmi = "\u{feff}FileType=text/acmi/tacview\nFileVersion=2.2\n#1\n1,T=1|2|3\n";
+    let mut p = Parser::new(acmi.as_bytes()).unwrap();
+    assert_eq!(p.bytes_read(), 46);
+    p.next().unwrap().unwrap();
+    assert_eq!(p.bytes_read(), 49);
+    p.next().unwrap().unwrap();
+    assert!(p.next().is_none());
+    assert_eq!(p.bytes_read(), acmi.len() as u64);
+}
+
+#[test]
+fn test_checkpoint() {
+    let acmi = "FileType=text/acmi/tacview\nFileVersion=2.1\n0,ReferenceLongitude=10\n\
+                #0\n1,T=1|2|3\n#1\n1,T=2|2|3\n1,T=x\n";
+    let mut p = Parser::new(Cursor::new(acmi))
+        .unwrap()
+        .with_reference_resolution();
+    for _ in 0..3 {
+        p.next().unwrap().unwrap();
+    }
+    let checkpoint = p.checkpoint().unwrap();
+    let rest = p.collect::<Vec<_>>();
+
+    let resumed = Parser::resume(Cursor::new(acmi), checkpoint)
+        .unwrap()
+        .with_reference_resolution();
+    assert_eq!(resumed.file_version(), (2, 1));
+    let resumed = resumed.collect::<Vec<_>>();
+    assert_eq!(resumed.len(), 3);
+    assert_eq!(resumed[0].as_ref().unwrap(), rest[0].as_ref().unwrap());
+    assert_eq!(resumed[1].as_ref().unwrap(), rest[1].as_ref().unwrap());
+    assert!(matches!(resumed[2], Err(ParseError::At { line: 8, .. })));
+    assert_eq!(
+        resumed[1].as_ref().unwrap(),
+        &Record::Update(Update {
+            id: 1,
+            props: vec![Property::T(
+                crate::record::Coords::default().position(2.0, 12.0, 3.0)
+            )],
+        })
+    );
+
+    let utf16 = include_bytes!("../tests/fixtures/utf16le.txt.acmi");
+    assert!(Parser::new(&utf16[..]).unwrap().checkpoint().is_none());
+}
+
+#[test]
+fn test_next_ref() {
+    let acmi = r#"FileType=text/acmi/tacview
+FileVersion=2.2
+0,Title=Test
+// comment
+#1
+1,T=1|2|3,Name=F-16C
+0,Event=Destroyed|1|
+-1"#;
+    let mut p = Parser::new(acmi.as_bytes()).unwrap();
+    let mut records = Vec::new();
+    while let Some(record) = p.next_ref() {
+        records.push(record.unwrap().to_owned().unwrap());
+    }
+
+    let expected = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(records, expected);
+    assert_eq!(records.len(), 5);
+}
+
+#[test]
+fn test_lenient() {
+    let acmi = r#"FileType=text/acmi/tacview
+FileVersion=2.2
+#1
+1,T=1|2|3
+2,T=garbage
+#2
+1,T=4|5|6"#;
+    let mut p = Parser::new(acmi.as_bytes()).unwrap().lenient();
+    let records = p.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[3].to_string(), "1,T=4|5|6");
+    assert!(matches!(p.skipped(), [ParseError::At { line: 5, .. }]));
+}
+
+#[test]
+fn test_record_kind_filters() {
+    let acmi = r#"FileType=text/acmi/tacview
+FileVersion=2.2
+#1
+1,T=1|2|3
+0,Event=Destroyed|1|
+#2.5
+-1"#;
+    let parser = || Parser::new(acmi.as_bytes()).unwrap();
+    assert_eq!(
+        parser().frames().collect::<Result<Vec<_>, _>>().unwrap(),
+        vec![1.0, 2.5]
+    );
+    assert_eq!(parser().updates().count(), 1);
+    assert_eq!(
+        parser().events().next().unwrap().unwrap().source_id(),
+        Some(1)
+    );
+}
+
+#[test]
+fn test_from_zip_member() {
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, title) in [("a.txt.acmi", "A"), ("b.txt.acmi", "B")] {
+        zip.start_file(name, SimpleFileOptions::default()).unwrap();
+        write!(
+            zip,
+            "FileType=text/acmi/tacview\nFileVersion=2.2\n0,Title={title}\n"
+        )
+        .unwrap();
+    }
+    let archive = ZipArchive::new(zip.finish().unwrap()).unwrap();
+
+    let mut parser = Parser::from_zip_member(archive.clone(), "b.txt.acmi").unwrap();
+    assert_eq!(
+        parser.next().unwrap().unwrap(),
+        Record::GlobalProperty(GlobalProperty::Title("B".to_string()))
+    );
+    assert!(matches!(
+        Parser::from_zip_member(archive, "c.txt.acmi"),
+        Err(ParseError::Zip(ZipError::FileNotFound))
+    ));
+}
+
+#[test]
+fn test_from_zip_archive() {
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    zip.add_directory("tracks/", SimpleFileOptions::default())
+        .unwrap();
+    for (name, title) in [("tracks/a.txt.acmi", "A"), ("b.ACMI", "B")] {
+        zip.start_file(name, SimpleFileOptions::default()).unwrap();
+        write!(
+            zip,
+            "FileType=text/acmi/tacview\nFileVersion=2.2\n0,Title={title}\n"
+        )
+        .unwrap();
+    }
+    zip.start_file("readme.txt", SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(b"not a recording").unwrap();
+    let archive = ZipArchive::new(zip.finish().unwrap()).unwrap();
+
+    let tracks = Parser::from_zip_archive(archive)
+        .map(|entry| {
+            let (name, mut parser) = entry.unwrap();
+            (name, parser.next().unwrap().unwrap())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tracks,
+        vec![
+            (
+                "tracks/a.txt.acmi".to_string(),
+                Record::GlobalProperty(GlobalProperty::Title("A".to_string()))
+            ),
+            (
+                "b.ACMI".to_string(),
+                Record::GlobalProperty(GlobalProperty::Title("B".to_string()))
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_auto() {
+    use crate::Writer;
+
+    let records = vec![
+        Record::GlobalProperty(crate::record::GlobalProperty::Title("Test".to_string())),
+        Record::Frame(1.0),
+        Record::Remove(1),
+    ];
+
+    let mut plain = Writer::new(Vec::new()).unwrap();
+    plain.write_all(records.clone()).unwrap();
+    let mut plain = Cursor::new(plain.into_inner());
+
+    let mut compressed = Cursor::new(Vec::new());
+    Writer::new_compressed(&mut compressed)
+        .unwrap()
+        .write_all(records.clone())
+        .unwrap();
+    compressed.set_position(0);
+
+    for rd in [&mut plain, &mut compressed] {
+        let parsed = Parser::auto(rd)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(parsed, records);
+    }
+
+    assert!(matches!(
+        Parser::auto(&mut Cursor::new(b"PK")),
+        Err(ParseError::InvalidFileType)
+    ));
+}
+
+#[test]
+fn test_utf16() {
+    let records = |acmi: &[u8]| {
+        Parser::new(acmi)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    };
+
+    let expected = vec![
+        Record::GlobalProperty(crate::record::GlobalProperty::Title("Übung 🛩".to_string())),
+        Record::GlobalProperty(crate::record::GlobalProperty::Comments(
+            "multi\r\nline".to_string(),
+        )),
+        Record::Frame(1.5),
+    ];
+
+    let utf16le = include_bytes!("../tests/fixtures/utf16le.txt.acmi");
+    assert_eq!(records(utf16le), expected);
+
+    let utf16be = utf16le
+        .chunks(2)
+        .flat_map(|unit| [unit[1], unit[0]])
+        .collect::<Vec<_>>();
+    assert_eq!(records(&utf16be), expected);
+
+    // code units and surrogate pairs split across reads
+    struct Chunked<'a>(&'a [u8]);
+    impl Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = self.0.len().min(buf.len()).min(3);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+    assert_eq!(
+        Parser::new(Chunked(utf16le))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        expected
+    );
+}
+
+#[test]
+fn test_coordinate_validation() {
+    let acmi = r#"FileType=text/acmi/tacview
+FileVersion=2.2
+0,ReferenceLongitude=170
+0,ReferenceLatitude=40
+1,T=5|5|1000
+2,T=15|5|1000
+3,T=5|55|1000
+"#;
+    let records = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .with_coordinate_validation()
+        .collect::<Vec<_>>();
+    assert!(records[2].is_ok());
+    assert!(matches!(
+        &records[3],
+        Err(ParseError::At { line: 6, source })
+            if matches!(**source, ParseError::InvalidCoordinates(CoordError::Longitude(v)) if v == 185.0)
+    ));
+    assert!(matches!(
+        &records[4],
+        Err(ParseError::At { line: 7, source })
+            if matches!(**source, ParseError::InvalidCoordinates(CoordError::Latitude(v)) if v == 95.0)
+    ));
+
+    let mut p = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .with_reference_resolution()
+        .with_coordinate_validation()
+        .lenient();
+    assert_eq!(p.by_ref().count(), 3);
+    assert_eq!(p.skipped().len(), 2);
+}
+
+#[test]
+#[cfg(feature = "flate2")]
+fn test_gzip() {
+    let plain = Parser::new(&include_bytes!("../tests/fixtures/track.txt.acmi")[..])
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let gzip = Parser::new_gzip(&include_bytes!("../tests/fixtures/track.acmi.gz")[..])
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(plain.len(), 7);
+    assert_eq!(gzip, plain);
+}
+
+#[test]
+fn test_monotonic_frames() {
+    let acmi = "FileType=text/acmi/tacview\nFileVersion=2.2\n#1\n#2\n#1.5\n#2\n#3\n";
+    let frames = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .with_monotonic_frames()
+        .collect::<Vec<_>>();
+    assert_eq!(frames.len(), 5);
+    assert!(matches!(
+        &frames[2],
+        Err(ParseError::At { line: 5, source })
+            if matches!(**source, ParseError::NonMonotonicFrame { previous, time } if previous == 2.0 && time == 1.5)
+    ));
+
+    let mut p = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .with_monotonic_frames()
+        .lenient();
+    assert_eq!(
+        p.by_ref().collect::<Result<Vec<_>, _>>().unwrap(),
+        vec![
+            Record::Frame(1.0),
+            Record::Frame(2.0),
+            Record::Frame(2.0),
+            Record::Frame(3.0)
+        ]
+    );
+    assert_eq!(p.skipped().len(), 1);
+
+    // not checked by default
+    assert_eq!(Parser::new(acmi.as_bytes()).unwrap().frames().count(), 5);
+}
+
+#[test]
+fn test_strict_events() {
+    let acmi = "FileType=text/acmi/tacview\nFileVersion=2.2\n0,Event=Landed|1|\n0,Event=Foo|1|\n";
+    let records = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .with_strict_events()
+        .collect::<Vec<_>>();
+    assert!(records[0].is_ok());
+    assert!(matches!(
+        &records[1],
+        Err(ParseError::At { line: 4, source }) if matches!(&**source, ParseError::UnknownEvent(name) if name == "Foo")
+    ));
+
+    // not checked by default
+    assert_eq!(Parser::new(acmi.as_bytes()).unwrap().events().count(), 2);
+}
+
+#[test]
+fn test_unique_properties() {
+    let acmi = "FileType=text/acmi/tacview\nFileVersion=2.2\n\
+                3,HDG=10,FuelWeight=1,FuelWeight2=2,Foo=1,Bar=2\n3,HDG=10,HDG=20\n3,Foo=1,Foo=2\n";
+    let records = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .with_unique_properties()
+        .collect::<Vec<_>>();
+    assert!(records[0].is_ok());
+    assert!(matches!(
+        &records[1],
+        Err(ParseError::At { line: 4, source }) if matches!(&**source, ParseError::DuplicateProperty(name) if name == "HDG")
+    ));
+    assert!(matches!(
+        &records[2],
+        Err(ParseError::At { line: 5, source }) if matches!(&**source, ParseError::DuplicateProperty(name) if name == "Foo")
+    ));
+
+    // not checked by default
+    let updates = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .updates()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        updates[1].props,
+        vec![Property::HDG(10.0), Property::HDG(20.0)]
+    );
+}
+
+#[test]
+fn test_into_records() {
+    let acmi = "FileType=text/acmi/tacview\nFileVersion=2.2\n#0\n1,T=1|2|3\n-1\n";
+    let records = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .into_records()
+        .unwrap();
+    assert_eq!(records.len(), 3);
+
+    let presized = Parser::new(Cursor::new(acmi))
+        .unwrap()
+        .into_records_presized()
+        .unwrap();
+    assert_eq!(presized, records);
+
+    let invalid = "FileType=text/acmi/tacview\nFileVersion=2.2\n#0\n1,T=1\n-1\n";
+    assert!(matches!(
+        Parser::new(Cursor::new(invalid))
+            .unwrap()
+            .into_records_presized(),
+        Err(ParseError::At { line: 4, .. })
+    ));
+}
@@ -0,0 +1,203 @@
+use std::collections::VecDeque;
+use std::io;
+use std::mem;
+
+use crate::parser::{check_file_type, check_version, is_banner_line, lines, parse_line};
+use crate::record::Record;
+use crate::ParseError;
+
+/// Parser that is fed the input in chunks of arbitrary size (e.g. as received from a socket),
+/// instead of reading it from a [`std::io::Read`]. Partial lines are buffered until they are
+/// complete.
+#[derive(Debug)]
+pub struct PushParser {
+    buf: Vec<u8>,
+    splitter: lines::LineSplitter,
+    line: usize,
+    /// The number of the first physical line of the line that is currently buffered.
+    start_line: usize,
+    state: State,
+    records: VecDeque<Result<Record, ParseError>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    FileType,
+    Version,
+    Records,
+    Done,
+}
+
+impl PushParser {
+    /// Creates a new parser. The file type and version are checked once the first lines are fed.
+    pub fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            splitter: lines::LineSplitter::default(),
+            line: 0,
+            start_line: 1,
+            state: State::FileType,
+            records: VecDeque::new(),
+        }
+    }
+
+    /// Parses all lines completed by `bytes` and returns their records (and errors).
+    pub fn feed(
+        &mut self,
+        mut bytes: &[u8],
+    ) -> impl Iterator<Item = Result<Record, ParseError>> + '_ {
+        while self.state != State::Done && !bytes.is_empty() {
+            if self.buf.is_empty() {
+                self.start_line = self.line + 1;
+            }
+
+            let (n, complete) = self.splitter.split(bytes, &mut self.buf);
+            bytes = &bytes[n..];
+            if !complete {
+                continue;
+            }
+            self.line += 1;
+
+            let Some(mut line) = self.take_line() else {
+                break;
+            };
+            if lines::finish_line(&mut line) {
+                self.handle_line(&line);
+            } else {
+                self.buf = line.into_bytes();
+            }
+        }
+
+        self.records.drain(..)
+    }
+
+    /// Signals the end of the input. Parses the last line if it was not terminated by a line
+    /// break and returns its record (or error).
+    pub fn finish(&mut self) -> impl Iterator<Item = Result<Record, ParseError>> + '_ {
+        if self.state != State::Done && !self.buf.is_empty() {
+            if let Some(mut line) = self.take_line() {
+                lines::finish_last_line(&mut line);
+                self.handle_line(&line);
+            }
+        }
+
+        match mem::replace(&mut self.state, State::Done) {
+            State::FileType => self.records.push_back(Err(ParseError::InvalidFileType)),
+            State::Version => self.records.push_back(Err(ParseError::InvalidVersion)),
+            State::Records | State::Done => {}
+        }
+
+        self.records.drain(..)
+    }
+
+    fn take_line(&mut self) -> Option<String> {
+        match String::from_utf8(mem::take(&mut self.buf)) {
+            Ok(line) => Some(line),
+            Err(err) => {
+                self.state = State::Done;
+                self.records.push_back(Err(ParseError::Io(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    err,
+                ))));
+                None
+            }
+        }
+    }
+
+    fn handle_line(&mut self, line: &str) {
+        match self.state {
+            State::FileType if is_banner_line(line) => {}
+            State::FileType => match check_file_type(line) {
+                Ok(()) => self.state = State::Version,
+                Err(err) => self.fail(err),
+            },
+            State::Version => match check_version(line) {
+                Ok(_) => self.state = State::Records,
+                Err(err) => self.fail(err),
+            },
+            State::Records => {
+                if line.is_empty() {
+                    self.state = State::Done;
+                    return;
+                }
+
+                match parse_line(line) {
+                    Ok(Some(record)) => self.records.push_back(Ok(record)),
+                    Ok(None) => {}
+                    Err(err) => self.records.push_back(Err(ParseError::At {
+                        line: self.start_line,
+                        source: Box::new(err),
+                    })),
+                }
+            }
+            State::Done => {}
+        }
+    }
+
+    fn fail(&mut self, err: ParseError) {
+        self.state = State::Done;
+        self.records.push_back(Err(err));
+    }
+}
+
+impl Default for PushParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Parser;
+
+    #[test]
+    fn test_byte_by_byte() {
+        for acmi in [
+            "FileType=text/acmi/tacview\r\nFileVersion=2.2\r\n0,Comments=1\\\r\n2\r\n#1\r\n1,T=1|2|3,Name=F-16C\r\n2,T=1\r\n#2",
+            "FileType=text/acmi/tacview\rFileVersion=2.2\r0,Comments=1\\\r2\r#1\r-2a\r2,T=1\r#2\r",
+        ] {
+            let mut parser = PushParser::new();
+            let mut records = Vec::new();
+            for b in acmi.as_bytes() {
+                records.extend(parser.feed(std::slice::from_ref(b)));
+            }
+            records.extend(parser.finish());
+
+            let expected = Parser::new(acmi.as_bytes()).unwrap().collect::<Vec<_>>();
+            assert_eq!(records.len(), expected.len());
+            for (record, expected) in records.into_iter().zip(expected) {
+                match (record, expected) {
+                    (Ok(record), Ok(expected)) => assert_eq!(record, expected),
+                    (
+                        Err(ParseError::At { line, .. }),
+                        Err(ParseError::At {
+                            line: expected_line,
+                            ..
+                        }),
+                    ) => assert_eq!(line, expected_line),
+                    (record, expected) => panic!("expected {expected:?}, got {record:?}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_file_type() {
+        let mut parser = PushParser::new();
+        assert!(parser.feed(b"FileType=text/acmi").next().is_none());
+        assert!(matches!(
+            parser.finish().collect::<Vec<_>>().as_slice(),
+            [Err(ParseError::InvalidFileType)]
+        ));
+    }
+
+    #[test]
+    fn test_banner() {
+        let mut parser = PushParser::new();
+        let records = parser
+            .feed(b"// banner\n\nFileType=text/acmi/tacview\nFileVersion=2.2\n#1\n")
+            .collect::<Vec<_>>();
+        assert!(matches!(records.as_slice(), [Ok(Record::Frame(_))]));
+    }
+}
@@ -0,0 +1,250 @@
+//! Tacview's real-time telemetry protocol, which streams the same line format as ACMI files over a
+//! TCP connection after a short handshake. The host (e.g. a simulator) listens for connections
+//! (by default on port 42674) and Tacview connects to it as a client.
+
+use std::io::{self, Read, Write};
+
+use crate::record::Record;
+use crate::{ParseError, Parser, Writer};
+
+const STREAM_VERSION: &str = "XtraLib.Stream.0";
+const PROTOCOL_VERSION: &str = "Tacview.RealTimeTelemetry.0";
+
+/// Upper bound for the size of a handshake, to not read forever from misbehaving peers.
+const MAX_HANDSHAKE_LEN: usize = 4096;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RealTimeError {
+    #[error("invalid real-time telemetry handshake")]
+    InvalidHandshake,
+    #[error("failed to parse telemetry")]
+    Parse(#[from] ParseError),
+    #[error("error reading or writing telemetry")]
+    Io(#[from] io::Error),
+}
+
+/// The host side of a real-time telemetry connection, streaming records to a connected client
+/// (e.g. Tacview).
+pub struct RealTimeWriter<S> {
+    writer: Writer<S>,
+    client_name: String,
+    password_hash: String,
+}
+
+/// The client side of a real-time telemetry connection, reading records streamed by a host.
+pub struct RealTimeReader<S> {
+    parser: Parser<S>,
+    host_name: String,
+}
+
+impl<S> RealTimeWriter<S>
+where
+    S: Read + Write,
+{
+    /// Performs the handshake on a freshly accepted connection, announcing the host as
+    /// `host_name`, and writes the file header. The password hash sent by the client is not
+    /// checked, see [`RealTimeWriter::password_hash`].
+    pub fn accept(mut stream: S, host_name: &str) -> Result<Self, RealTimeError> {
+        write!(
+            stream,
+            "{STREAM_VERSION}\n{PROTOCOL_VERSION}\n{host_name}\n\0"
+        )?;
+        stream.flush()?;
+
+        let handshake = read_handshake(&mut stream)?;
+        let [client_name, password_hash] = check_handshake(&handshake)?;
+        let (client_name, password_hash) = (client_name.to_string(), password_hash.to_string());
+
+        Ok(Self {
+            writer: Writer::new(stream)?,
+            client_name,
+            password_hash,
+        })
+    }
+
+    /// The name the client identified itself with.
+    pub fn client_name(&self) -> &str {
+        &self.client_name
+    }
+
+    /// The password hash sent by the client, for hosts that want to restrict access.
+    pub fn password_hash(&self) -> &str {
+        &self.password_hash
+    }
+
+    /// Writes a record, see [`Writer::write`].
+    pub fn write(&mut self, record: impl Into<Record>) -> Result<(), io::Error> {
+        self.writer.write(record)
+    }
+
+    /// Writes all records in order, stopping at the first error, see [`Writer::write_all`].
+    pub fn write_all<I>(&mut self, records: I) -> Result<(), io::Error>
+    where
+        I: IntoIterator,
+        I::Item: Into<Record>,
+    {
+        self.writer.write_all(records)
+    }
+
+    pub fn into_inner(self) -> S {
+        self.writer.into_inner()
+    }
+}
+
+impl<S> RealTimeReader<S>
+where
+    S: Read + Write,
+{
+    /// Performs the handshake on a freshly established connection to a host, identifying as
+    /// `client_name` with the given `password_hash`, and reads the file header.
+    pub fn connect(
+        mut stream: S,
+        client_name: &str,
+        password_hash: &str,
+    ) -> Result<Self, RealTimeError> {
+        let handshake = read_handshake(&mut stream)?;
+        let [host_name, ""] = check_handshake(&handshake)? else {
+            return Err(RealTimeError::InvalidHandshake);
+        };
+        let host_name = host_name.to_string();
+
+        write!(
+            stream,
+            "{STREAM_VERSION}\n{PROTOCOL_VERSION}\n{client_name}\n{password_hash}\0"
+        )?;
+        stream.flush()?;
+
+        Ok(Self {
+            parser: Parser::new(stream)?,
+            host_name,
+        })
+    }
+
+    /// The name the host identified itself with.
+    pub fn host_name(&self) -> &str {
+        &self.host_name
+    }
+
+    pub fn into_parser(self) -> Parser<S> {
+        self.parser
+    }
+}
+
+impl<S> Iterator for RealTimeReader<S>
+where
+    S: Read,
+{
+    type Item = Result<Record, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parser.next()
+    }
+}
+
+/// Reads a handshake up to (and excluding) its terminating NUL byte. Reads byte by byte to not
+/// consume any of the telemetry following the handshake.
+fn read_handshake(stream: &mut impl Read) -> Result<String, RealTimeError> {
+    let mut handshake = Vec::new();
+    let mut byte = [0; 1];
+    while handshake.len() <= MAX_HANDSHAKE_LEN {
+        match stream.read(&mut byte)? {
+            0 => break,
+            _ if byte[0] == 0 => {
+                return String::from_utf8(handshake).map_err(|_| RealTimeError::InvalidHandshake)
+            }
+            _ => handshake.push(byte[0]),
+        }
+    }
+    Err(RealTimeError::InvalidHandshake)
+}
+
+/// Checks the protocol versions of a handshake and returns its remaining two lines.
+fn check_handshake(handshake: &str) -> Result<[&str; 2], RealTimeError> {
+    let mut lines = handshake.split('\n');
+    if lines.next() != Some(STREAM_VERSION) || lines.next() != Some(PROTOCOL_VERSION) {
+        return Err(RealTimeError::InvalidHandshake);
+    }
+    match (lines.next(), lines.next(), lines.next()) {
+        (Some(a), Some(b), None) => Ok([a, b]),
+        _ => Err(RealTimeError::InvalidHandshake),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// An in-memory connection, reading from `rd` and writing to `wr`.
+    struct Connection {
+        rd: Cursor<Vec<u8>>,
+        wr: Vec<u8>,
+    }
+
+    impl Connection {
+        fn new(input: &str) -> Self {
+            Self {
+                rd: Cursor::new(input.as_bytes().to_vec()),
+                wr: Vec::new(),
+            }
+        }
+    }
+
+    impl Read for Connection {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.rd.read(buf)
+        }
+    }
+
+    impl Write for Connection {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.wr.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_writer() {
+        let conn = Connection::new("XtraLib.Stream.0\nTacview.RealTimeTelemetry.0\nViewer\n0\0");
+        let mut wr = RealTimeWriter::accept(conn, "Simulator").unwrap();
+        assert_eq!(wr.client_name(), "Viewer");
+        assert_eq!(wr.password_hash(), "0");
+        wr.write(Record::Frame(1.0)).unwrap();
+        assert_eq!(
+            String::from_utf8(wr.into_inner().wr).unwrap(),
+            "XtraLib.Stream.0\nTacview.RealTimeTelemetry.0\nSimulator\n\0\
+             FileType=text/acmi/tacview\nFileVersion=2.2\n#1\n"
+        );
+    }
+
+    #[test]
+    fn test_reader() {
+        let conn = Connection::new(
+            "XtraLib.Stream.0\nTacview.RealTimeTelemetry.0\nSimulator\n\0\
+             FileType=text/acmi/tacview\nFileVersion=2.2\n#1\n-2a\n",
+        );
+        let mut rd = RealTimeReader::connect(conn, "Viewer", "0").unwrap();
+        assert_eq!(rd.host_name(), "Simulator");
+        assert_eq!(rd.next().unwrap().unwrap(), Record::Frame(1.0));
+        assert_eq!(rd.next().unwrap().unwrap(), Record::Remove(0x2a));
+        assert!(rd.next().is_none());
+    }
+
+    #[test]
+    fn test_invalid_handshake() {
+        for input in [
+            "XtraLib.Stream.0\nTacview.RealTimeTelemetry.1\nViewer\n0\0",
+            "XtraLib.Stream.0\nTacview.RealTimeTelemetry.0\nViewer\0",
+            "XtraLib.Stream.0\nTacview.RealTimeTelemetry.0\nViewer\n0",
+        ] {
+            assert!(matches!(
+                RealTimeWriter::accept(Connection::new(input), "Simulator"),
+                Err(RealTimeError::InvalidHandshake)
+            ));
+        }
+    }
+}
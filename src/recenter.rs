@@ -0,0 +1,131 @@
+//! Rewrites recordings with absolute coordinates to coordinates relative to a reference point,
+//! which considerably reduces the size of the written file.
+
+use std::io::{self, Read, Write};
+
+use crate::record::{GlobalProperty, Property, Record};
+use crate::{ParseError, Parser, Writer};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecenterError {
+    #[error("failed to parse recording")]
+    Parse(#[from] ParseError),
+    #[error("error writing recording")]
+    Io(#[from] io::Error),
+}
+
+/// Writes all records of `parser` to `writer`, with object coordinates relative to `reference`
+/// (`(longitude, latitude)`). Reference global properties already present in the input are
+/// resolved and replaced.
+///
+/// If no `reference` is given, the median longitude and latitude of all object coordinates is
+/// used. As this requires two passes over the recording, all records are read into memory first.
+/// Pass a `reference` to stream the records instead.
+///
+/// Consider configuring `writer` with [`Writer::with_precision`], as subtracting the reference
+/// can introduce floating point noise in the written coordinates.
+pub fn recenter<R: Read, W: Write>(
+    parser: Parser<R>,
+    writer: Writer<W>,
+    reference: Option<(f64, f64)>,
+) -> Result<Writer<W>, RecenterError> {
+    let records = parser.with_reference_resolution().filter(|record| {
+        !matches!(
+            record,
+            Ok(Record::GlobalProperty(
+                GlobalProperty::ReferenceLongitude(_) | GlobalProperty::ReferenceLatitude(_)
+            ))
+        )
+    });
+
+    if let Some((longitude, latitude)) = reference {
+        return write(records, writer.with_reference(longitude, latitude));
+    }
+
+    let records = records.collect::<Result<Vec<_>, _>>()?;
+    let mut longitudes = Vec::new();
+    let mut latitudes = Vec::new();
+    for record in &records {
+        let Record::Update(update) = record else {
+            continue;
+        };
+        for prop in &update.props {
+            if let Property::T(coords) = prop {
+                longitudes.extend(coords.longitude);
+                latitudes.extend(coords.latitude);
+            }
+        }
+    }
+
+    let writer = match (median(&mut longitudes), median(&mut latitudes)) {
+        (Some(longitude), Some(latitude)) => writer.with_reference(longitude, latitude),
+        _ => writer,
+    };
+    write(records.into_iter().map(Ok), writer)
+}
+
+fn write<W: Write>(
+    records: impl Iterator<Item = Result<Record, ParseError>>,
+    mut writer: Writer<W>,
+) -> Result<Writer<W>, RecenterError> {
+    for record in records {
+        writer.write(record?)?;
+    }
+    Ok(writer)
+}
+
+fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    Some(values[values.len() / 2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recenter() {
+        let acmi = r#"FileType=text/acmi/tacview
+FileVersion=2.2
+0,Title=Absolute
+#0
+1,T=41.5|42.5|1000
+2,T=41.25|42.25|2000
+3,T=41.75|42.75|3000
+#1
+1,T=41.5|42|1000
+"#;
+        let recenter = |reference| {
+            let writer = recenter(
+                Parser::new(acmi.as_bytes()).unwrap(),
+                Writer::new(Vec::new()).unwrap(),
+                reference,
+            )
+            .unwrap();
+            String::from_utf8(writer.into_inner()).unwrap()
+        };
+
+        assert_eq!(
+            recenter(None),
+            r#"FileType=text/acmi/tacview
+FileVersion=2.2
+0,ReferenceLongitude=41.5
+0,ReferenceLatitude=42.5
+0,Title=Absolute
+#0
+1,T=0|0|1000
+2,T=-0.25|-0.25|2000
+3,T=0.25|0.25|3000
+#1
+1,T=0|-0.5|1000
+"#
+        );
+
+        let with_reference = recenter(Some((41.0, 42.0)));
+        assert!(with_reference.contains("0,ReferenceLongitude=41\n0,ReferenceLatitude=42\n"));
+        assert!(with_reference.contains("1,T=0.5|0.5|1000\n"));
+    }
+}
@@ -0,0 +1,291 @@
+use core::fmt::Display;
+use core::str::FromStr;
+
+use super::{
+    parse_id, parse_numeric, Event, GlobalProperty, Property, PropertyKind, Record, Update,
+};
+use crate::ParseError;
+
+/// A record borrowing its text values from the line it was parsed from. Use
+/// [`RecordRef::to_owned`] to fully parse it into a [`Record`].
+///
+/// Global properties, events and object properties are written exactly as they were read (e.g.
+/// `IAS=12.30` is not normalized to `IAS=12.3`), which allows byte-exact round trips of their
+/// values. Object ids and frame times are parsed, and thus written normalized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordRef<'a> {
+    GlobalProperty {
+        name: &'a str,
+        value: &'a str,
+    },
+    /// The raw event, i.e. everything after `0,Event=`.
+    Event(&'a str),
+    Remove(u64),
+    Frame(f64),
+    Update(UpdateRef<'a>),
+}
+
+/// An object update whose properties are only split, but not parsed, on demand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateRef<'a> {
+    pub id: u64,
+    props: &'a str,
+}
+
+/// A property as it appears in the input. The `value` is still escaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PropertyRef<'a> {
+    pub name: &'a str,
+    pub value: &'a str,
+}
+
+/// An iterator over the properties of an [`UpdateRef`].
+#[derive(Debug, Clone)]
+pub struct PropertiesRef<'a> {
+    rest: &'a str,
+}
+
+impl<'a> RecordRef<'a> {
+    /// Parses a single line. Returns `None` for comment lines.
+    pub fn parse(line: &'a str) -> Result<Option<Self>, ParseError> {
+        let mut chars = line.chars();
+        match chars.next().ok_or(ParseError::Eol)? {
+            '-' => {
+                let id = parse_id("object id", line[1..].trim_end())?;
+                Ok(Some(RecordRef::Remove(id)))
+            }
+            '#' => {
+                let id = parse_numeric("frame time", line[1..].trim_end())?;
+                Ok(Some(RecordRef::Frame(id)))
+            }
+            '/' if chars.next() == Some('/') => Ok(None),
+            _ => {
+                let (id, rest) = line.split_once(',').ok_or(ParseError::Eol)?;
+                let id = parse_id("object id", id)?;
+
+                // id `0` is reserved for global properties and events, however it is written
+                Ok(Some(if id == 0 {
+                    let (name, value) = rest
+                        .split_once('=')
+                        .ok_or(ParseError::MissingDelimiter('='))?;
+                    if name == "Event" {
+                        RecordRef::Event(value)
+                    } else {
+                        RecordRef::GlobalProperty { name, value }
+                    }
+                } else {
+                    RecordRef::Update(UpdateRef { id, props: rest })
+                }))
+            }
+        }
+    }
+
+    /// Fully parses the record into its owned representation.
+    pub fn to_owned(&self) -> Result<Record, ParseError> {
+        Ok(match *self {
+            RecordRef::GlobalProperty { name, value } => {
+                Record::GlobalProperty(GlobalProperty::parse(name, value)?)
+            }
+            RecordRef::Event(value) => Record::Event(Event::from_str(value)?),
+            RecordRef::Remove(id) => Record::Remove(id),
+            RecordRef::Frame(time) => Record::Frame(time),
+            RecordRef::Update(update) => Record::Update(update.to_owned()?),
+        })
+    }
+}
+
+impl<'a> UpdateRef<'a> {
+    pub(crate) fn new(id: u64, props: &'a str) -> Self {
+        Self { id, props }
+    }
+
+    pub fn props(&self) -> PropertiesRef<'a> {
+        PropertiesRef { rest: self.props }
+    }
+
+    /// Fully parses the update and all its properties into its owned representation.
+    pub fn to_owned(&self) -> Result<Update, ParseError> {
+        Ok(Update {
+            id: self.id,
+            props: self
+                .props()
+                .map(|p| p.and_then(|p| p.to_owned()))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl PropertyRef<'_> {
+    /// The kind of the property, determined by its name only (i.e. without parsing its value).
+    pub fn kind(&self) -> PropertyKind {
+        PropertyKind::from(self.name)
+    }
+
+    /// Fully parses the property into its owned representation.
+    pub fn to_owned(&self) -> Result<Property, ParseError> {
+        Property::parse(self.name, self.value)
+    }
+}
+
+impl Display for RecordRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            RecordRef::GlobalProperty { name, value } => write!(f, "0,{name}={value}"),
+            RecordRef::Event(event) => write!(f, "0,Event={event}"),
+            RecordRef::Remove(id) => write!(f, "-{id:x}"),
+            RecordRef::Frame(time) => write!(f, "#{time}"),
+            RecordRef::Update(update) => update.fmt(f),
+        }
+    }
+}
+
+impl Display for UpdateRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:x},{}", self.id, self.props)
+    }
+}
+
+impl Display for PropertyRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}={}", self.name, self.value)
+    }
+}
+
+impl<'a> Iterator for PropertiesRef<'a> {
+    type Item = Result<PropertyRef<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        let mut end = self.rest.len();
+        let mut escaped = false;
+        for (i, ch) in self.rest.char_indices() {
+            if escaped {
+                escaped = false;
+                continue;
+            }
+
+            match ch {
+                '\\' => escaped = true,
+                ',' => {
+                    end = i;
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        let kv = &self.rest[..end];
+        self.rest = self.rest.get(end + 1..).unwrap_or_default();
+
+        Some(
+            kv.split_once('=')
+                .map(|(name, value)| PropertyRef { name, value })
+                .ok_or(ParseError::MissingDelimiter('=')),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::record::Coords;
+
+    #[test]
+    fn test_trailing_whitespace() {
+        assert_eq!(
+            RecordRef::parse("-1a3 ").unwrap(),
+            Some(RecordRef::Remove(0x1a3))
+        );
+        assert_eq!(
+            RecordRef::parse("#123.4 \t").unwrap(),
+            Some(RecordRef::Frame(123.4))
+        );
+    }
+
+    #[test]
+    fn test_zero_id() {
+        for line in ["0,Title=Test", "00,Title=Test", "000,Title=Test"] {
+            assert_eq!(
+                RecordRef::parse(line).unwrap(),
+                Some(RecordRef::GlobalProperty {
+                    name: "Title",
+                    value: "Test"
+                })
+            );
+        }
+        assert_eq!(
+            RecordRef::parse("00,Event=Message|1|Hello").unwrap(),
+            Some(RecordRef::Event("Message|1|Hello"))
+        );
+        assert!(RecordRef::parse("0x0,Title=Test").is_err());
+    }
+
+    #[test]
+    fn test_verbatim_display() {
+        for line in [
+            "3f02,T=1.50|2|1e3,IAS=12.30,Label=a\\,b",
+            "0,ReferenceLongitude=41.00",
+            "0,Event=Timeout|SourceId:507|Bullseye:50/15000/2500.0|",
+            "-1a3",
+            "#12.5",
+        ] {
+            let record = RecordRef::parse(line).unwrap().unwrap();
+            assert_eq!(record.to_string(), line);
+        }
+
+        let record = RecordRef::parse("3f02,IAS=12.30").unwrap().unwrap();
+        let RecordRef::Update(update) = record else {
+            unreachable!()
+        };
+        assert_eq!(
+            update.props().next().unwrap().unwrap().to_string(),
+            "IAS=12.30"
+        );
+    }
+
+    #[test]
+    fn test_update_ref() {
+        let record = RecordRef::parse("3f02,T=1|2|3,Label=a\\,b,HDG=90")
+            .unwrap()
+            .unwrap();
+        let RecordRef::Update(update) = record else {
+            panic!("expected update, got {record:?}");
+        };
+        assert_eq!(update.id, 0x3f02);
+        assert_eq!(
+            update.props().collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![
+                PropertyRef {
+                    name: "T",
+                    value: "1|2|3"
+                },
+                PropertyRef {
+                    name: "Label",
+                    value: "a\\,b"
+                },
+                PropertyRef {
+                    name: "HDG",
+                    value: "90"
+                },
+            ]
+        );
+        assert_eq!(
+            record.to_owned().unwrap(),
+            Record::Update(Update {
+                id: 0x3f02,
+                props: vec![
+                    Property::T(Coords::default().position(2.0, 1.0, 3.0)),
+                    Property::Label("a,b".to_string()),
+                    Property::HDG(90.0),
+                ]
+            })
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use core::fmt::Display;
+
+use crate::record::Escaped;
+
+/// Common ISO 3166-1 alpha-2 country codes. Codes are parsed case-insensitively and written in
+/// lowercase (e.g. `us`), as in the ACMI specification. All other codes are kept as
+/// [`Country::Unknown`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Country {
+    Argentina,
+    Armenia,
+    Australia,
+    Austria,
+    Azerbaijan,
+    Belarus,
+    Belgium,
+    Brazil,
+    Bulgaria,
+    Canada,
+    Chile,
+    China,
+    Croatia,
+    Czechia,
+    Denmark,
+    Egypt,
+    Finland,
+    France,
+    Georgia,
+    Germany,
+    Greece,
+    Hungary,
+    India,
+    Indonesia,
+    Iran,
+    Iraq,
+    Israel,
+    Italy,
+    Japan,
+    Jordan,
+    Kazakhstan,
+    Kuwait,
+    Lebanon,
+    Libya,
+    Malaysia,
+    Mexico,
+    Netherlands,
+    NewZealand,
+    NorthKorea,
+    Norway,
+    Oman,
+    Pakistan,
+    Poland,
+    Portugal,
+    Qatar,
+    Romania,
+    Russia,
+    SaudiArabia,
+    Serbia,
+    Slovakia,
+    SouthAfrica,
+    SouthKorea,
+    Spain,
+    Sweden,
+    Switzerland,
+    Syria,
+    Taiwan,
+    Thailand,
+    Turkey,
+    Ukraine,
+    UnitedArabEmirates,
+    UnitedKingdom,
+    UnitedStates,
+    Vietnam,
+    Yemen,
+    Unknown(String),
+}
+
+impl From<&str> for Country {
+    fn from(s: &str) -> Self {
+        match s.to_ascii_lowercase().as_str() {
+            "ar" => Self::Argentina,
+            "am" => Self::Armenia,
+            "au" => Self::Australia,
+            "at" => Self::Austria,
+            "az" => Self::Azerbaijan,
+            "by" => Self::Belarus,
+            "be" => Self::Belgium,
+            "br" => Self::Brazil,
+            "bg" => Self::Bulgaria,
+            "ca" => Self::Canada,
+            "cl" => Self::Chile,
+            "cn" => Self::China,
+            "hr" => Self::Croatia,
+            "cz" => Self::Czechia,
+            "dk" => Self::Denmark,
+            "eg" => Self::Egypt,
+            "fi" => Self::Finland,
+            "fr" => Self::France,
+            "ge" => Self::Georgia,
+            "de" => Self::Germany,
+            "gr" => Self::Greece,
+            "hu" => Self::Hungary,
+            "in" => Self::India,
+            "id" => Self::Indonesia,
+            "ir" => Self::Iran,
+            "iq" => Self::Iraq,
+            "il" => Self::Israel,
+            "it" => Self::Italy,
+            "jp" => Self::Japan,
+            "jo" => Self::Jordan,
+            "kz" => Self::Kazakhstan,
+            "kw" => Self::Kuwait,
+            "lb" => Self::Lebanon,
+            "ly" => Self::Libya,
+            "my" => Self::Malaysia,
+            "mx" => Self::Mexico,
+            "nl" => Self::Netherlands,
+            "nz" => Self::NewZealand,
+            "kp" => Self::NorthKorea,
+            "no" => Self::Norway,
+            "om" => Self::Oman,
+            "pk" => Self::Pakistan,
+            "pl" => Self::Poland,
+            "pt" => Self::Portugal,
+            "qa" => Self::Qatar,
+            "ro" => Self::Romania,
+            "ru" => Self::Russia,
+            "sa" => Self::SaudiArabia,
+            "rs" => Self::Serbia,
+            "sk" => Self::Slovakia,
+            "za" => Self::SouthAfrica,
+            "kr" => Self::SouthKorea,
+            "es" => Self::Spain,
+            "se" => Self::Sweden,
+            "ch" => Self::Switzerland,
+            "sy" => Self::Syria,
+            "tw" => Self::Taiwan,
+            "th" => Self::Thailand,
+            "tr" => Self::Turkey,
+            "ua" => Self::Ukraine,
+            "ae" => Self::UnitedArabEmirates,
+            "gb" => Self::UnitedKingdom,
+            "us" => Self::UnitedStates,
+            "vn" => Self::Vietnam,
+            "ye" => Self::Yemen,
+            _ => Self::Unknown(s.to_string()),
+        }
+    }
+}
+
+impl Country {
+    /// The uppercase ISO 3166-1 alpha-2 code (e.g. `US`), or the code as it was parsed for
+    /// [`Country::Unknown`].
+    pub fn code_upper(&self) -> Cow<'_, str> {
+        match self {
+            Country::Unknown(code) => Cow::Borrowed(code),
+            country => Cow::Owned(country.code().to_ascii_uppercase()),
+        }
+    }
+
+    /// The lowercase ISO 3166-1 alpha-2 code, or the code as it was parsed for
+    /// [`Country::Unknown`].
+    pub fn code(&self) -> &str {
+        use Country::*;
+        match self {
+            Argentina => "ar",
+            Armenia => "am",
+            Australia => "au",
+            Austria => "at",
+            Azerbaijan => "az",
+            Belarus => "by",
+            Belgium => "be",
+            Brazil => "br",
+            Bulgaria => "bg",
+            Canada => "ca",
+            Chile => "cl",
+            China => "cn",
+            Croatia => "hr",
+            Czechia => "cz",
+            Denmark => "dk",
+            Egypt => "eg",
+            Finland => "fi",
+            France => "fr",
+            Georgia => "ge",
+            Germany => "de",
+            Greece => "gr",
+            Hungary => "hu",
+            India => "in",
+            Indonesia => "id",
+            Iran => "ir",
+            Iraq => "iq",
+            Israel => "il",
+            Italy => "it",
+            Japan => "jp",
+            Jordan => "jo",
+            Kazakhstan => "kz",
+            Kuwait => "kw",
+            Lebanon => "lb",
+            Libya => "ly",
+            Malaysia => "my",
+            Mexico => "mx",
+            Netherlands => "nl",
+            NewZealand => "nz",
+            NorthKorea => "kp",
+            Norway => "no",
+            Oman => "om",
+            Pakistan => "pk",
+            Poland => "pl",
+            Portugal => "pt",
+            Qatar => "qa",
+            Romania => "ro",
+            Russia => "ru",
+            SaudiArabia => "sa",
+            Serbia => "rs",
+            Slovakia => "sk",
+            SouthAfrica => "za",
+            SouthKorea => "kr",
+            Spain => "es",
+            Sweden => "se",
+            Switzerland => "ch",
+            Syria => "sy",
+            Taiwan => "tw",
+            Thailand => "th",
+            Turkey => "tr",
+            Ukraine => "ua",
+            UnitedArabEmirates => "ae",
+            UnitedKingdom => "gb",
+            UnitedStates => "us",
+            Vietnam => "vn",
+            Yemen => "ye",
+            Unknown(code) => code,
+        }
+    }
+}
+
+impl Display for Country {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", Escaped(self.code()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_country() {
+        assert_eq!(Country::from("us"), Country::UnitedStates);
+        assert_eq!(Country::from("GB"), Country::UnitedKingdom);
+        assert_eq!(Country::from("Ru"), Country::Russia);
+        assert_eq!(Country::UnitedStates.to_string(), "us");
+        assert_eq!(Country::from("XX"), Country::Unknown("XX".to_string()));
+        assert_eq!(Country::from("XX").to_string(), "XX");
+        assert_eq!(Country::from(""), Country::Unknown(String::new()));
+        assert_eq!(Country::UnitedStates.code_upper(), "US");
+        assert_eq!(Country::from("xx").code_upper(), "xx");
+    }
+}
@@ -1,9 +1,15 @@
-use std::fmt::Display;
-use std::str::FromStr;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt::Display;
+use core::str::FromStr;
 
+use crate::record::{unescape, Escaped};
 use crate::ParseError;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Event {
     pub kind: EventKind,
     pub params: Vec<String>,
@@ -11,6 +17,8 @@ pub struct Event {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum EventKind {
     /// Generic event.
     Message,
@@ -55,6 +63,16 @@ pub enum EventKind {
     Unknown(String),
 }
 
+/// Assembles the parameters of an [`Event`] in the layout Tacview expects for its kind, see
+/// [`Event::builder`].
+#[derive(Debug, Clone)]
+pub struct EventBuilder {
+    kind: EventKind,
+    ids: [Option<u64>; 2],
+    params: Vec<String>,
+    text: Option<String>,
+}
+
 impl FromStr for Event {
     type Err = ParseError;
 
@@ -73,7 +91,7 @@ impl FromStr for Event {
             name => EventKind::Unknown(name.to_string()),
         };
 
-        let mut params = parts.map(String::from).collect::<Vec<_>>();
+        let mut params = parts.map(|p| unescape(p).into_owned()).collect::<Vec<_>>();
         let text = if params.is_empty() {
             None
         } else {
@@ -85,16 +103,155 @@ impl FromStr for Event {
 }
 
 impl Display for Event {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "0,Event={}", self.kind.as_str())?;
         for param in &self.params {
-            write!(f, "|{param}")?;
+            write!(f, "|{}", Escaped(param))?;
+        }
+        // The text is the last parameter, thus it has to be written (even if empty) whenever there
+        // are params, as otherwise the last param would be read back as text.
+        if !self.params.is_empty() || self.text.is_some() {
+            write!(f, "|{}", Escaped(self.text.as_deref().unwrap_or_default()))?;
         }
-        write!(f, "|{}", self.text.as_deref().unwrap_or_default())?;
         Ok(())
     }
 }
 
+impl Event {
+    pub fn builder(kind: EventKind) -> EventBuilder {
+        EventBuilder {
+            kind,
+            ids: [None; 2],
+            params: Vec::new(),
+            text: None,
+        }
+    }
+
+    /// The id of the object the event is about (e.g. the destroyed object), or for shot events
+    /// (`Timeout`) the object which has fired the weapon.
+    pub fn source_id(&self) -> Option<u64> {
+        self.param("SourceId")
+            .or_else(|| self.positional_param(0))
+            .and_then(|id| u64::from_str_radix(id, 16).ok())
+    }
+
+    /// The id of the target of the event, e.g. the target of a shot.
+    pub fn target_id(&self) -> Option<u64> {
+        self.param("TargetId")
+            .or_else(|| self.positional_param(1))
+            .and_then(|id| u64::from_str_radix(id, 16).ok())
+    }
+
+    /// The ids of all objects the event mentions, i.e. all params that are hex object ids, as well
+    /// as the `SourceId` and `TargetId` params of shot events (`Timeout`), in the order they
+    /// appear. Like for [`Event::param`], a `SourceId` or `TargetId` in the text (for events
+    /// without a trailing `|`) is included too. Other params and any other text are skipped.
+    pub fn object_ids(&self) -> Vec<u64> {
+        let keyed = |p: &String| match p.split_once(':') {
+            Some(("SourceId" | "TargetId", id)) => u64::from_str_radix(id, 16).ok(),
+            _ => None,
+        };
+        self.params
+            .iter()
+            .filter_map(|p| {
+                if p.contains(':') {
+                    keyed(p)
+                } else {
+                    u64::from_str_radix(p, 16).ok()
+                }
+            })
+            .chain(self.text.iter().filter_map(keyed))
+            .collect()
+    }
+
+    /// The optional bullseye coordinates of shot events (`Timeout`). They are always in meters,
+    /// even if Tacview displays the result in nautical miles. Returns `None` if the event has no
+    /// (or a malformed) `Bullseye` param.
+    /// Unit: m
+    pub fn bullseye(&self) -> Option<(f64, f64, f64)> {
+        let mut parts = self
+            .param("Bullseye")?
+            .split('/')
+            .map(|v| f64::from_str(v).ok());
+        let bullseye = (parts.next()??, parts.next()??, parts.next()??);
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(bullseye)
+    }
+
+    /// The value of a `Key:Value` parameter, as used by shot events (`Timeout`).
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .chain(self.text.iter())
+            .find_map(|p| p.strip_prefix(key)?.strip_prefix(':'))
+    }
+
+    fn positional_param(&self, index: usize) -> Option<&str> {
+        self.params
+            .get(index)
+            .map(String::as_str)
+            .filter(|p| !p.contains(':'))
+    }
+}
+
+impl EventBuilder {
+    /// The object the event is about, or for shot events (`Timeout`) the object which has fired
+    /// the weapon.
+    pub fn source_id(mut self, id: u64) -> Self {
+        if self.kind == EventKind::Timeout {
+            self.params.push(format!("SourceId:{id:x}"));
+        } else {
+            self.ids[0] = Some(id);
+        }
+        self
+    }
+
+    /// The target of the event, e.g. the target of a shot.
+    pub fn target_id(mut self, id: u64) -> Self {
+        if self.kind == EventKind::Timeout {
+            self.params.push(format!("TargetId:{id:x}"));
+        } else {
+            self.ids[1] = Some(id);
+        }
+        self
+    }
+
+    /// Adds a `Key:Value` parameter, as used by shot events (`Timeout`), e.g. `AmmoType:FOX2`.
+    pub fn param(mut self, key: &str, value: impl Display) -> Self {
+        self.params.push(format!("{key}:{value}"));
+        self
+    }
+
+    /// The bullseye coordinates of shot events (`Timeout`).
+    /// Unit: m
+    pub fn bullseye(self, x: f64, y: f64, z: f64) -> Self {
+        self.param("Bullseye", format_args!("{x}/{y}/{z}"))
+    }
+
+    pub fn text(mut self, text: &str) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+
+    pub fn build(self) -> Event {
+        let ids = match self.ids {
+            [None, None] => Vec::new(),
+            [Some(source), None] => vec![format!("{source:x}")],
+            [source, Some(target)] => vec![
+                source.map(|id| format!("{id:x}")).unwrap_or_default(),
+                format!("{target:x}"),
+            ],
+        };
+        Event {
+            kind: self.kind,
+            params: ids.into_iter().chain(self.params).collect(),
+            text: self.text,
+        }
+    }
+}
+
 impl EventKind {
     fn as_str(&self) -> &str {
         use EventKind::*;
@@ -128,4 +285,103 @@ mod tests {
             "0,Event=Landed|1|2|"
         )
     }
+
+    #[test]
+    fn test_unknown_event_roundtrip() {
+        for event in [
+            "FancyNewThing|1a|2b",
+            "FancyNewThing|1a|",
+            "FancyNewThing|text",
+            "FancyNewThing",
+        ] {
+            let parsed = Event::from_str(event).unwrap();
+            assert_eq!(parsed.kind, EventKind::Unknown("FancyNewThing".to_string()));
+            assert_eq!(parsed.to_string(), format!("0,Event={event}"));
+        }
+    }
+
+    #[test]
+    fn test_typed_params() {
+        let event = Event::from_str("Destroyed|6A56|").unwrap();
+        assert_eq!(event.source_id(), Some(0x6a56));
+        assert_eq!(event.target_id(), None);
+        assert_eq!(event.bullseye(), None);
+
+        let event = Event::from_str(
+            "Timeout|SourceId:507|AmmoType:FOX2|AmmoCount:1|Bullseye:50/15000/2500|TargetId:201|Outcome:Kill",
+        )
+        .unwrap();
+        assert_eq!(event.source_id(), Some(0x507));
+        assert_eq!(event.target_id(), Some(0x201));
+        assert_eq!(event.bullseye(), Some((50.0, 15000.0, 2500.0)));
+        assert_eq!(event.param("Outcome"), Some("Kill"));
+
+        let event = Event::from_str("Bookmark|Starting precautionary landing practice").unwrap();
+        assert_eq!(event.source_id(), None);
+
+        let event = Event::from_str("Timeout|SourceId:507|Bullseye:50/15000|").unwrap();
+        assert_eq!(event.bullseye(), None);
+        let event = Event::from_str("Timeout|SourceId:507|Bullseye:50/15000/2500/1|").unwrap();
+        assert_eq!(event.bullseye(), None);
+        let event = Event::from_str("Timeout|Bullseye:-1.5/2e3/0.25").unwrap();
+        assert_eq!(event.bullseye(), Some((-1.5, 2000.0, 0.25)));
+    }
+
+    #[test]
+    fn test_object_ids() {
+        let event = Event::from_str("LeftArea|1a|2b||zz|3c|Left the area").unwrap();
+        assert_eq!(event.object_ids(), vec![0x1a, 0x2b, 0x3c]);
+
+        let event = Event::from_str("Timeout|SourceId:507|AmmoType:FOX2|TargetId:201|Outcome:Kill")
+            .unwrap();
+        assert_eq!(event.object_ids(), vec![0x507, 0x201]);
+
+        // without a trailing `|`, the last param is read as text
+        let event = Event::from_str("Timeout|SourceId:507|TargetId:201").unwrap();
+        assert_eq!(event.target_id(), Some(0x201));
+        assert_eq!(event.object_ids(), vec![0x507, 0x201]);
+
+        let event = Event::from_str("Message|1a|2b").unwrap();
+        assert_eq!(event.object_ids(), vec![0x1a]);
+
+        let event = Event::from_str("Bookmark|Starting precautionary landing practice").unwrap();
+        assert!(event.object_ids().is_empty());
+    }
+
+    #[test]
+    fn test_builder() {
+        let event = Event::builder(EventKind::Timeout)
+            .source_id(0x507)
+            .param("AmmoType", "FOX2")
+            .param("AmmoCount", 1)
+            .bullseye(50.0, 15000.0, 2500.0)
+            .target_id(0x201)
+            .param("IntendedTarget", "Leader")
+            .param("Outcome", "Kill")
+            .build();
+        assert_eq!(
+            event.to_string(),
+            "0,Event=Timeout|SourceId:507|AmmoType:FOX2|AmmoCount:1|Bullseye:50/15000/2500|TargetId:201|IntendedTarget:Leader|Outcome:Kill|"
+        );
+        assert_eq!(event.source_id(), Some(0x507));
+        assert_eq!(event.target_id(), Some(0x201));
+        assert_eq!(event.bullseye(), Some((50.0, 15000.0, 2500.0)));
+
+        let event = Event::builder(EventKind::Destroyed)
+            .source_id(0x6a56)
+            .build();
+        assert_eq!(event.to_string(), "0,Event=Destroyed|6a56|");
+        assert_eq!(event.source_id(), Some(0x6a56));
+
+        let event = Event::builder(EventKind::Message)
+            .target_id(0x3301)
+            .text("Because of your reckless flying, you are grounded")
+            .build();
+        assert_eq!(
+            event.to_string(),
+            "0,Event=Message||3301|Because of your reckless flying\\, you are grounded"
+        );
+        assert_eq!(event.source_id(), None);
+        assert_eq!(event.target_id(), Some(0x3301));
+    }
 }
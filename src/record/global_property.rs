@@ -1,10 +1,13 @@
-use std::fmt::Display;
-use std::str::FromStr;
+use alloc::string::{String, ToString};
+use core::fmt::Display;
+use core::str::FromStr;
 
-use crate::record::Precision;
+use crate::record::{parse_numeric, unescape, Escaped, Precision};
 use crate::ParseError;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum GlobalProperty {
     /// Source simulator, control station or file format.
     DataSource(String),
@@ -56,50 +59,128 @@ impl FromStr for GlobalProperty {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (name, value) = s.split_once('=').ok_or(ParseError::MissingDelimiter('='))?;
+        Self::parse(name, value)
+    }
+}
 
+impl GlobalProperty {
+    pub(crate) fn parse(name: &str, value: &str) -> Result<Self, ParseError> {
         Ok(match name {
-            "DataSource" => Self::DataSource(value.to_string()),
-            "DataRecorder" => Self::DataRecorder(value.to_string()),
-            "ReferenceTime" => Self::ReferenceTime(value.to_string()),
-            "RecordingTime" => Self::RecordingTime(value.to_string()),
-            "Author" => Self::Author(value.to_string()),
-            "Title" => Self::Title(value.to_string()),
-            "Category" => Self::Category(value.to_string()),
-            "Briefing" => Self::Briefing(value.to_string()),
-            "Debriefing" => Self::Debriefing(value.to_string()),
-            "Comments" => Self::Comments(value.to_string()),
-            "ReferenceLongitude" => Self::ReferenceLongitude(value.parse()?),
-            "ReferenceLatitude" => Self::ReferenceLatitude(value.parse()?),
-            name => Self::Unknown(name.to_string(), value.to_string()),
+            "DataSource" => Self::DataSource(unescape(value).into_owned()),
+            "DataRecorder" => Self::DataRecorder(unescape(value).into_owned()),
+            "ReferenceTime" => Self::ReferenceTime(unescape(value).into_owned()),
+            "RecordingTime" => Self::RecordingTime(unescape(value).into_owned()),
+            "Author" => Self::Author(unescape(value).into_owned()),
+            "Title" => Self::Title(unescape(value).into_owned()),
+            "Category" => Self::Category(unescape(value).into_owned()),
+            "Briefing" => Self::Briefing(unescape(value).into_owned()),
+            "Debriefing" => Self::Debriefing(unescape(value).into_owned()),
+            "Comments" => Self::Comments(unescape(value).into_owned()),
+            "ReferenceLongitude" => {
+                Self::ReferenceLongitude(parse_numeric("ReferenceLongitude", value)?)
+            }
+            "ReferenceLatitude" => {
+                Self::ReferenceLatitude(parse_numeric("ReferenceLatitude", value)?)
+            }
+            name => Self::Unknown(name.to_string(), unescape(value).into_owned()),
         })
     }
 }
 
 impl Display for GlobalProperty {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         use GlobalProperty::*;
         match self {
-            DataSource(v) => write!(f, "0,DataSource={v}"),
-            DataRecorder(v) => write!(f, "0,DataRecorder={v}"),
-            ReferenceTime(v) => write!(f, "0,ReferenceTime={v}"),
-            RecordingTime(v) => write!(f, "0,RecordingTime={v}"),
-            Author(v) => write!(f, "0,Author={v}"),
-            Title(v) => write!(f, "0,Title={v}"),
-            Category(v) => write!(f, "0,Category={v}"),
-            Briefing(v) => write!(f, "0,Briefing={v}"),
-            Debriefing(v) => write!(f, "0,Debriefing={v}"),
-            Comments(v) => write!(
-                f,
-                "0,Comments={}",
-                v.replace("\r\n", "\\\r\n").replace('\n', "\\\n")
-            ),
+            DataSource(v) => write!(f, "0,DataSource={}", Escaped(v)),
+            DataRecorder(v) => write!(f, "0,DataRecorder={}", Escaped(v)),
+            ReferenceTime(v) => write!(f, "0,ReferenceTime={}", Escaped(v)),
+            RecordingTime(v) => write!(f, "0,RecordingTime={}", Escaped(v)),
+            Author(v) => write!(f, "0,Author={}", Escaped(v)),
+            Title(v) => write!(f, "0,Title={}", Escaped(v)),
+            Category(v) => write!(f, "0,Category={}", Escaped(v)),
+            Briefing(v) => write!(f, "0,Briefing={}", Escaped(v)),
+            Debriefing(v) => write!(f, "0,Debriefing={}", Escaped(v)),
+            Comments(v) => write!(f, "0,Comments={}", Escaped(v)),
             ReferenceLongitude(v) => write!(f, "0,ReferenceLongitude={}", v.max_precision(7)),
             ReferenceLatitude(v) => write!(f, "0,ReferenceLatitude={}", v.max_precision(7)),
-            Unknown(v, _) => write!(f, "0,Unknown={v}"),
+            Unknown(name, value) => write!(f, "0,{name}={}", Escaped(value)),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl GlobalProperty {
+    /// Creates a [`GlobalProperty::ReferenceTime`] from a typed UTC timestamp.
+    pub fn from_reference_time(time: chrono::DateTime<chrono::Utc>) -> Self {
+        Self::ReferenceTime(format_time(time))
+    }
+
+    /// Creates a [`GlobalProperty::RecordingTime`] from a typed UTC timestamp.
+    pub fn from_recording_time(time: chrono::DateTime<chrono::Utc>) -> Self {
+        Self::RecordingTime(format_time(time))
+    }
+
+    /// The parsed timestamp of a [`GlobalProperty::ReferenceTime`]. Returns `None` for any other
+    /// property or if the timestamp is not well-formed.
+    pub fn reference_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        match self {
+            Self::ReferenceTime(v) => parse_time(v),
+            _ => None,
+        }
+    }
+
+    /// The parsed timestamp of a [`GlobalProperty::RecordingTime`]. Returns `None` for any other
+    /// property or if the timestamp is not well-formed.
+    pub fn recording_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        match self {
+            Self::RecordingTime(v) => parse_time(v),
+            _ => None,
         }
     }
 }
 
+#[cfg(feature = "chrono")]
+fn format_time(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+}
+
+#[cfg(feature = "chrono")]
+fn parse_time(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|t| t.to_utc())
+        .or_else(|_| {
+            // Timestamps without any timezone are considered to be UTC.
+            chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f").map(|t| t.and_utc())
+        })
+        .ok()
+}
+
+#[cfg(all(test, feature = "chrono"))]
+#[test]
+fn test_reference_time() {
+    use chrono::{TimeZone, Timelike, Utc};
+
+    let p = GlobalProperty::from_str("ReferenceTime=2023-02-16T02:45:54.958Z").unwrap();
+    let time = p.reference_time().unwrap();
+    assert_eq!(
+        time,
+        Utc.with_ymd_and_hms(2023, 2, 16, 2, 45, 54)
+            .unwrap()
+            .with_nanosecond(958_000_000)
+            .unwrap()
+    );
+    assert_eq!(GlobalProperty::from_reference_time(time), p);
+
+    let p = GlobalProperty::from_str("RecordingTime=2023-02-16T02:45:54").unwrap();
+    assert_eq!(
+        p.recording_time(),
+        Some(Utc.with_ymd_and_hms(2023, 2, 16, 2, 45, 54).unwrap())
+    );
+
+    let p = GlobalProperty::from_str("ReferenceTime=yesterday").unwrap();
+    assert_eq!(p.reference_time(), None);
+}
+
 #[test]
 fn test_multi_line_comment() {
     let comment = GlobalProperty::Comments(
@@ -110,3 +191,13 @@ fn test_multi_line_comment() {
     );
     assert_eq!(comment.to_string(), "0,Comments=1\\\n2\\\n3");
 }
+
+#[test]
+fn test_unknown_roundtrip() {
+    let p = GlobalProperty::from_str("Foo=Bar\\,Baz").unwrap();
+    assert_eq!(
+        p,
+        GlobalProperty::Unknown("Foo".to_string(), "Bar,Baz".to_string())
+    );
+    assert_eq!(p.to_string(), "0,Foo=Bar\\,Baz");
+}
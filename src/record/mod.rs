@@ -1,16 +1,43 @@
+mod borrowed;
+mod country;
 mod event;
 mod global_property;
 mod property;
 mod update;
 
-use std::fmt::Display;
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use core::fmt::{Display, Write};
+use core::str::FromStr;
+use core::time::Duration;
 
-pub use event::{Event, EventKind};
+pub use borrowed::{PropertiesRef, PropertyRef, RecordRef, UpdateRef};
+pub use country::Country;
+pub use event::{Event, EventBuilder, EventKind};
 pub use global_property::GlobalProperty;
-pub use property::{Color, Coords, Property, Tag};
+pub use property::{Coalition, Color, CoordError, Coords, Mode, Property, PropertyKind, Tag};
 pub use update::Update;
 
+use crate::{math, ParseError};
+
+/// The default number of decimal places frame times are written with (i.e. millisecond
+/// resolution).
+pub(crate) const FRAME_PRECISION: u32 = 3;
+
+/// A single record (line) of an ACMI file.
+///
+/// With the `arbitrary` feature, all record types implement `arbitrary::Arbitrary` to generate
+/// random records, e.g. for fuzzing. Note that not all generated records survive a write and parse
+/// round trip, e.g. because frame times and reference coordinates are rounded when written, or
+/// because event params and text cannot contain a `|`.
+///
+/// Records (like all record types) implement `PartialEq` by comparing all their values exactly.
+/// Numeric values are `f64`s compared with `==`, so e.g. a `NaN` value never equals itself and
+/// values that only differ after being rounded (see [`crate::Writer::with_precision`]) do not
+/// compare equal. For the same reason, records do not implement `Eq`.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Record {
     GlobalProperty(GlobalProperty),
     Event(Event),
@@ -19,18 +46,86 @@ pub enum Record {
     Update(Update),
 }
 
+/// The kind of a [`Record`], without its payload, see [`Record::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RecordKind {
+    GlobalProperty,
+    Event,
+    Remove,
+    Frame,
+    Update,
+}
+
+impl Record {
+    /// Parses a single line of an ACMI file (without the `FileType` and `FileVersion` header).
+    /// Returns `None` for comment lines.
+    pub fn parse(line: &str) -> Result<Option<Self>, ParseError> {
+        RecordRef::parse(line)?
+            .map(|record| record.to_owned())
+            .transpose()
+    }
+
+    /// The kind of the record, e.g. to count or dispatch records without matching their payload.
+    pub fn kind(&self) -> RecordKind {
+        match self {
+            Record::GlobalProperty(_) => RecordKind::GlobalProperty,
+            Record::Event(_) => RecordKind::Event,
+            Record::Remove(_) => RecordKind::Remove,
+            Record::Frame(_) => RecordKind::Frame,
+            Record::Update(_) => RecordKind::Update,
+        }
+    }
+
+    /// The id of the object an [`Record::Update`] or [`Record::Remove`] is about, `None` for all
+    /// other records.
+    pub fn object_id(&self) -> Option<u64> {
+        match self {
+            Record::Update(update) => Some(update.id),
+            Record::Remove(id) => Some(*id),
+            Record::GlobalProperty(_) | Record::Event(_) | Record::Frame(_) => None,
+        }
+    }
+
+    /// The time of a [`Record::Frame`], relative to the reference time. Returns `None` for all
+    /// other records, and for negative (or otherwise unrepresentable) frame times.
+    pub fn frame_duration(&self) -> Option<Duration> {
+        match self {
+            Record::Frame(secs) => Duration::try_from_secs_f64(*secs).ok(),
+            _ => None,
+        }
+    }
+}
+
 impl Display for Record {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Record::GlobalProperty(r) => r.fmt(f),
             Record::Event(r) => r.fmt(f),
-            Record::Remove(id) => write!(f, "-{id}"),
-            Record::Frame(n) => write!(f, "#{}", n.max_precision(2)),
+            Record::Remove(id) => write!(f, "-{id:x}"),
+            Record::Frame(n) => write!(f, "#{}", n.max_precision(FRAME_PRECISION)),
             Record::Update(r) => r.fmt(f),
         }
     }
 }
 
+impl FromStr for Record {
+    type Err = ParseError;
+
+    /// Like [`Record::parse`], but fails with [`ParseError::Comment`] for comment lines.
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        Record::parse(line)?.ok_or(ParseError::Comment)
+    }
+}
+
+impl TryFrom<&str> for Record {
+    type Error = ParseError;
+
+    fn try_from(line: &str) -> Result<Self, Self::Error> {
+        Record::from_str(line)
+    }
+}
+
 impl From<GlobalProperty> for Record {
     fn from(p: GlobalProperty) -> Self {
         Self::GlobalProperty(p)
@@ -49,14 +144,94 @@ impl From<Update> for Record {
     }
 }
 
-trait Precision {
+/// Escapes backslashes, commas and line breaks (including bare carriage returns) in a text value
+/// so that it survives being written to and read back from an ACMI file.
+struct Escaped<'a>(&'a str);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut chars = self.0.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' => f.write_str("\\\\")?,
+                ',' => f.write_str("\\,")?,
+                '\n' => f.write_str("\\\n")?,
+                '\r' if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                    f.write_str("\\\r\n")?
+                }
+                '\r' => f.write_str("\\\r")?,
+                ch => f.write_char(ch)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reverts the escaping of backslashes and commas. Escaped line breaks are already handled while
+/// reading the lines of the input, except for escaped bare carriage returns in lines that are
+/// parsed on their own (e.g. with [`Record::parse`]). Any other backslash is kept as is.
+fn unescape(s: &str) -> Cow<'_, str> {
+    if !s.contains('\\') {
+        return Cow::Borrowed(s);
+    }
+
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('\\', Some(next @ ('\\' | ',' | '\r'))) => {
+                unescaped.push(*next);
+                chars.next();
+            }
+            (ch, _) => unescaped.push(ch),
+        }
+    }
+    Cow::Owned(unescaped)
+}
+
+/// Parses a hexadecimal object id, `field` names what the id is used for in errors.
+pub(crate) fn parse_id(field: &'static str, value: &str) -> Result<u64, ParseError> {
+    u64::from_str_radix(value, 16).map_err(|source| ParseError::InvalidId {
+        field,
+        value: value.to_string(),
+        source,
+    })
+}
+
+/// Parses a numeric value, `field` names the value in errors.
+pub(crate) fn parse_numeric(field: &'static str, value: &str) -> Result<f64, ParseError> {
+    f64::from_str(value).map_err(|source| ParseError::InvalidNumeric {
+        field,
+        value: value.to_string(),
+        source,
+    })
+}
+
+pub(crate) trait Precision {
     fn max_precision(self, max_precision: u32) -> Self;
 }
 
 impl Precision for f64 {
     fn max_precision(self, max_precision: u32) -> Self {
-        let p = f64::from(10i32.pow(max_precision));
-        (self * p).round() / p
+        let p = math::powi(10.0, max_precision as i32);
+        math::round(self * p) / p
+    }
+}
+
+impl Precision for Record {
+    fn max_precision(self, max_precision: u32) -> Self {
+        match self {
+            Record::Update(mut update) => {
+                update.props = update
+                    .props
+                    .into_iter()
+                    .map(|p| p.max_precision(max_precision))
+                    .collect();
+                Record::Update(update)
+            }
+            record => record,
+        }
     }
 }
 
@@ -68,7 +243,94 @@ impl Precision for Option<f64> {
 
 #[cfg(test)]
 mod test {
-    use super::Precision;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+    use core::time::Duration;
+
+    use super::{unescape, Escaped, Precision, Record};
+
+    #[test]
+    fn test_frame_duration() {
+        assert_eq!(
+            Record::Frame(12.25).frame_duration(),
+            Some(Duration::from_millis(12_250))
+        );
+        assert_eq!(Record::Frame(0.0).frame_duration(), Some(Duration::ZERO));
+        assert_eq!(Record::Frame(-1.5).frame_duration(), None);
+        assert_eq!(Record::Frame(f64::NAN).frame_duration(), None);
+        assert_eq!(Record::Remove(1).frame_duration(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "arbitrary")]
+    fn test_arbitrary() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        // xorshift, to get reproducible input bytes
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let bytes = (0..1 << 16)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect::<Vec<_>>();
+
+        // generated records can be written, and parsing them back does not panic
+        let mut u = Unstructured::new(&bytes);
+        while !u.is_empty() {
+            let record = Record::arbitrary(&mut u).unwrap();
+            let _ = crate::parser::parse_line(&record.to_string());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_kind() {
+        use super::RecordKind;
+
+        assert_eq!(Record::Remove(1).kind(), RecordKind::Remove);
+        assert_eq!(Record::Frame(1.0).kind(), RecordKind::Frame);
+        assert_eq!(
+            crate::parser::parse_line("1,T=1|2|3")
+                .unwrap()
+                .unwrap()
+                .kind(),
+            RecordKind::Update
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        use core::str::FromStr;
+
+        use crate::ParseError;
+
+        assert_eq!(Record::parse("#1.5").unwrap(), Some(Record::Frame(1.5)));
+        assert_eq!(Record::parse("// comment").unwrap(), None);
+        assert_eq!(Record::from_str("-2a").unwrap(), Record::Remove(0x2a));
+        assert_eq!(Record::try_from("-2a").unwrap(), Record::Remove(0x2a));
+        assert!(matches!(
+            Record::from_str("// comment"),
+            Err(ParseError::Comment)
+        ));
+        assert!(Record::parse("1,T=").is_err());
+    }
+
+    #[test]
+    fn test_object_id() {
+        assert_eq!(Record::Remove(0x2a).object_id(), Some(0x2a));
+        assert_eq!(
+            Record::Update(super::Update {
+                id: 0x3f02,
+                props: Vec::new()
+            })
+            .object_id(),
+            Some(0x3f02)
+        );
+        assert_eq!(Record::Frame(1.0).object_id(), None);
+    }
 
     #[test]
     #[allow(clippy::float_cmp)]
@@ -79,4 +341,37 @@ mod test {
         assert_eq!(12.3456789.max_precision(3), 12.346);
         assert_eq!(12.3.max_precision(6), 12.3);
     }
+
+    #[test]
+    fn test_escape_roundtrip() {
+        let value = "a,b\\c";
+        let escaped = Escaped(value).to_string();
+        assert_eq!(escaped, "a\\,b\\\\c");
+        assert_eq!(unescape(&escaped), value);
+
+        let value = "a\rb";
+        let escaped = Escaped(value).to_string();
+        assert_eq!(escaped, "a\\\rb");
+        assert_eq!(unescape(&escaped), value);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        use alloc::vec;
+
+        use super::{Coords, Property, Record, Tag, Update};
+
+        let record = Record::Update(Update {
+            id: 1,
+            props: vec![
+                Property::T(Coords::default().position(1.0, 2.0, 3.0)),
+                Property::Type([Tag::Air, Tag::FixedWing].into_iter().collect()),
+                Property::FuelWeight(1, 42.0),
+            ],
+        });
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains(r#""u":null"#));
+        assert_eq!(serde_json::from_str::<Record>(&json).unwrap(), record);
+    }
 }
@@ -1,14 +1,18 @@
 #![allow(clippy::upper_case_acronyms)]
 
-use std::borrow::Cow;
-use std::collections::HashSet;
-use std::fmt::Display;
-use std::str::FromStr;
+use alloc::borrow::Cow;
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Display;
+use core::str::FromStr;
 
-use crate::record::Precision;
-use crate::ParseError;
+use crate::record::{parse_id, parse_numeric, unescape, Country, Escaped, Precision};
+use crate::{math, ParseError};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Property {
     /// Object Coordinates.
     T(Coords),
@@ -22,7 +26,8 @@ pub enum Property {
     /// Object types are built using tags. This makes object management much more powerful and
     /// transparent than with the previous exclusive types. Type and Name are the only properties
     /// which *CANNOT* be predefined in Tacview database.
-    Type(HashSet<Tag>),
+    /// Tags are kept (and written) in the order they are declared in [`Tag`].
+    Type(BTreeSet<Tag>),
 
     /// Parent object id. Useful to associate for example a missile (child object) and
     /// its launcher aircraft (parent object).
@@ -54,10 +59,10 @@ pub enum Property {
     Group(String),
 
     /// ISO 3166-1 alpha-2 country code.
-    Country(String),
+    Country(Country),
 
     /// Coalition.
-    Coalition(String),
+    Coalition(Coalition),
 
     /// Color of the object.
     Color(Color),
@@ -199,19 +204,23 @@ pub enum Property {
     /// Unit: ratio
     DragChute(f64),
 
-    /// Fuel quantity currently available in each tanks (up to 10 tanks supported).
+    /// Fuel quantity currently available in each tanks (up to 10 tanks supported). The index is
+    /// zero-based, i.e. `FuelWeight2` has index 1.
     /// Unit: kg
     FuelWeight(u8, f64),
 
-    /// Fuel quantity currently available in each tanks (up to 10 tanks supported).
+    /// Fuel quantity currently available in each tanks (up to 10 tanks supported). The index is
+    /// zero-based, i.e. `FuelVolume2` has index 1.
     /// Unit: l
     FuelVolume(u8, f64),
 
-    /// Fuel flow for each engine (up to 8 engines supported).
+    /// Fuel flow for each engine (up to 8 engines supported). The index is zero-based, i.e.
+    /// `FuelFlowWeight2` has index 1.
     /// Unit: kg/hour
     FuelFlowWeight(u8, f64),
 
-    /// Fuel flow for each engine (up to 8 engines supported).
+    /// Fuel flow for each engine (up to 8 engines supported). The index is zero-based, i.e.
+    /// `FuelFlowVolume2` has index 1.
     /// Unit: l/hour
     FuelFlowVolume(u8, f64),
 
@@ -377,7 +386,107 @@ pub enum Property {
     Unknown(String, String),
 }
 
+/// The kind of a [`Property`], without its value, see [`Property::kind`]. Indexed properties
+/// (e.g. [`Property::FuelWeight`]) share one kind for all indices, as do all unknown properties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PropertyKind {
+    T,
+    Name,
+    Type,
+    Parent,
+    Next,
+    CallSign,
+    Registration,
+    Squawk,
+    ICAO24,
+    Pilot,
+    Group,
+    Country,
+    Coalition,
+    Color,
+    Shape,
+    Debug,
+    Label,
+    FocusedTarget,
+    LockedTarget,
+    Importance,
+    Slot,
+    Disabled,
+    Visible,
+    Health,
+    Length,
+    Width,
+    Height,
+    Radius,
+    IAS,
+    CAS,
+    TAS,
+    Mach,
+    AOA,
+    AOS,
+    AGL,
+    HDG,
+    HDM,
+    Throttle,
+    Throttle2,
+    Afterburner,
+    AirBrakes,
+    Flaps,
+    LandingGear,
+    LandingGearHandle,
+    Tailhook,
+    Parachute,
+    DragChute,
+    FuelWeight,
+    FuelVolume,
+    FuelFlowWeight,
+    FuelFlowVolume,
+    RadarMode,
+    RadarAzimuth,
+    RadarElevation,
+    RadarRoll,
+    RadarRange,
+    RadarHorizontalBeamwidth,
+    RadarVerticalBeamwidth,
+    LockedTargetMode,
+    LockedTargetAzimuth,
+    LockedTargetElevation,
+    LockedTargetRange,
+    EngagementMode,
+    EngagementMode2,
+    EngagementRange,
+    EngagementRange2,
+    VerticalEngagementRange,
+    VerticalEngagementRange2,
+    RollControlInput,
+    PitchControlInput,
+    YawControlInput,
+    RollControlPosition,
+    PitchControlPosition,
+    YawControlPosition,
+    RollTrimTab,
+    PitchTrimTab,
+    YawTrimTab,
+    AileronLeft,
+    AileronRight,
+    Elevator,
+    Rudder,
+    PilotHeadRoll,
+    PilotHeadPitch,
+    PilotHeadYaw,
+    VerticalGForce,
+    LongitudinalGForce,
+    LateralGForce,
+    ENL,
+    Unknown,
+}
+
+/// Object coordinates. All fields are optional, as updates only contain the values that changed.
+/// Coordinates are compared by exact `f64` equality of each field, see [`crate::record::Record`].
 #[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Coords {
     /// Unit: deg
     pub longitude: Option<f64>,
@@ -438,6 +547,60 @@ impl Coords {
         }
     }
 
+    /// Linearly interpolates between `self` (`t = 0`) and `other` (`t = 1`). Angles are
+    /// interpolated along the shortest direction. Fields that are `None` on either side are
+    /// `None` in the result.
+    pub fn lerp(&self, other: &Coords, t: f64) -> Coords {
+        let lerp = |a: Option<f64>, b: Option<f64>| Some(a? + (b? - a?) * t);
+        let lerp_angle = |a: Option<f64>, b: Option<f64>| {
+            let (a, b) = (a?, b?);
+            let delta = math::rem_euclid(b - a + 180.0, 360.0) - 180.0;
+            Some(a + delta * t)
+        };
+
+        Coords {
+            longitude: lerp(self.longitude, other.longitude),
+            latitude: lerp(self.latitude, other.latitude),
+            altitude: lerp(self.altitude, other.altitude),
+            u: lerp(self.u, other.u),
+            v: lerp(self.v, other.v),
+            roll: lerp_angle(self.roll, other.roll),
+            pitch: lerp_angle(self.pitch, other.pitch),
+            yaw: lerp_angle(self.yaw, other.yaw).map(|v| math::rem_euclid(v, 360.0)),
+            heading: lerp_angle(self.heading, other.heading).map(|v| math::rem_euclid(v, 360.0)),
+        }
+    }
+
+    /// A plain position, written as `T=lon|lat|alt`.
+    pub fn lla(lon: f64, lat: f64, alt: f64) -> Self {
+        Coords {
+            longitude: Some(lon),
+            latitude: Some(lat),
+            altitude: Some(alt),
+            ..Default::default()
+        }
+    }
+
+    /// A position with orientation, written as `T=lon|lat|alt|roll|pitch|yaw`.
+    pub fn with_orientation(lon: f64, lat: f64, alt: f64, roll: f64, pitch: f64, yaw: f64) -> Self {
+        Coords {
+            roll: Some(roll),
+            pitch: Some(pitch),
+            yaw: Some(yaw),
+            ..Coords::lla(lon, lat, alt)
+        }
+    }
+
+    /// A position in the native coordinates of a flat world, written as `T=||alt|u|v`.
+    pub fn flat(u: f64, v: f64, alt: f64) -> Self {
+        Coords {
+            altitude: Some(alt),
+            u: Some(u),
+            v: Some(v),
+            ..Default::default()
+        }
+    }
+
     pub fn position(mut self, lat: f64, lon: f64, alt: f64) -> Self {
         self.latitude = Some(lat);
         self.longitude = Some(lon);
@@ -462,20 +625,111 @@ impl Coords {
         self.heading = Some(v);
         self
     }
+
+    /// Great-circle distance (haversine) to `other`. If both coordinates have an altitude, the
+    /// slant range is returned instead. Returns `None` if either side lacks latitude or longitude.
+    /// Unit: m
+    pub fn distance_to(&self, other: &Coords) -> Option<f64> {
+        const EARTH_RADIUS: f64 = 6_371_008.8;
+
+        let lat1 = self.latitude?.to_radians();
+        let lat2 = other.latitude?.to_radians();
+        let delta_lat = lat2 - lat1;
+        let delta_lon = (other.longitude? - self.longitude?).to_radians();
+
+        let a = math::powi(math::sin(delta_lat / 2.0), 2)
+            + math::cos(lat1) * math::cos(lat2) * math::powi(math::sin(delta_lon / 2.0), 2);
+        let distance = 2.0 * EARTH_RADIUS * math::asin(math::sqrt(a));
+
+        Some(match (self.altitude, other.altitude) {
+            (Some(alt1), Some(alt2)) => math::hypot(distance, alt2 - alt1),
+            _ => distance,
+        })
+    }
+
+    /// Checks that latitude, longitude and altitude (if set) are within their valid ranges. As
+    /// coordinates are usually relative, the reference offsets are added to latitude and longitude
+    /// before checking them; pass `0.0` for absolute coordinates.
+    pub fn validate(
+        &self,
+        reference_latitude: f64,
+        reference_longitude: f64,
+    ) -> Result<(), CoordError> {
+        /// Deepest point of the ocean.
+        const MIN_ALTITUDE: f64 = -11_000.0;
+        /// Beyond the geostationary orbit.
+        const MAX_ALTITUDE: f64 = 40_000_000.0;
+
+        if let Some(latitude) = self.latitude.map(|v| v + reference_latitude) {
+            if !(-90.0..=90.0).contains(&latitude) {
+                return Err(CoordError::Latitude(latitude));
+            }
+        }
+        if let Some(longitude) = self.longitude.map(|v| v + reference_longitude) {
+            if !(-180.0..=180.0).contains(&longitude) {
+                return Err(CoordError::Longitude(longitude));
+            }
+        }
+        if let Some(altitude) = self.altitude {
+            if !(MIN_ALTITUDE..=MAX_ALTITUDE).contains(&altitude) {
+                return Err(CoordError::Altitude(altitude));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+pub enum CoordError {
+    #[error("latitude {0} is outside of -90..=90")]
+    Latitude(f64),
+    #[error("longitude {0} is outside of -180..=180")]
+    Longitude(f64),
+    #[error("altitude {0} m is outside of -11km..=40000km")]
+    Altitude(f64),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Color {
     Red,
     Orange,
+    Yellow,
     Green,
+    Cyan,
     Blue,
     Violet,
     Grey,
+    White,
+    /// Custom color, written as `#RRGGBB`.
+    Rgb(u8, u8, u8),
+    Unknown(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Coalition {
+    Allies,
+    Enemies,
+    Neutrals,
     Unknown(String),
 }
 
-#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+/// The state of a mode property ([`Property::RadarMode`], [`Property::LockedTargetMode`],
+/// [`Property::EngagementMode`] and [`Property::EngagementMode2`]), where `0` means off. See
+/// [`Property::mode`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Off,
+    /// The (non-zero) mode value.
+    On(f64),
+}
+
+#[derive(Debug, Hash, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Tag {
     // Class
     Air,
@@ -519,12 +773,14 @@ pub enum Tag {
     Parachutist,
     Shell,
     Bullet,
+    Grenade,
     Flare,
     Chaff,
     SmokeGrenade,
     Aerodrome,
     Container,
     Shrapnel,
+    Explosion,
     Unknown(String),
 }
 
@@ -533,159 +789,155 @@ impl FromStr for Property {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (name, value) = s.split_once('=').ok_or(ParseError::MissingDelimiter('='))?;
+        Self::parse(name, value)
+    }
+}
 
+impl Property {
+    pub(crate) fn parse(name: &str, value: &str) -> Result<Self, ParseError> {
+        let field = || PropertyKind::from(name).name();
+        let num = |value: &str| parse_numeric(field(), value);
+        let int_error = |value: &str, source| ParseError::InvalidId {
+            field: field(),
+            value: value.to_string(),
+            source,
+        };
+        let int = |value: &str| u64::from_str(value).map_err(|source| int_error(value, source));
+        let flag = |value: &str| {
+            i64::from_str(value)
+                .map(|v| v != 0)
+                .map_err(|source| int_error(value, source))
+        };
+        let id = |value: &str| parse_id(field(), value);
         Ok(match name {
             "T" => Property::T(Coords::from_str(value)?),
-            "Name" => Property::Name(value.to_string()),
-            "Type" => Property::Type(value.split('+').map(Tag::from).collect()),
-            "Parent" => Property::Parent(u64::from_str_radix(value, 16)?),
-            "Next" => Property::Next(u64::from_str_radix(value, 16)?),
-            "CallSign" => Property::CallSign(value.to_string()),
-            "Registration" => Property::Registration(value.to_string()),
-            "Squawk" => Property::Squawk(value.to_string()),
-            "ICAO24" => Property::ICAO24(value.to_string()),
-            "Pilot" => Property::Pilot(value.to_string()),
-            "Group" => Property::Group(value.to_string()),
-            "Country" => Property::Country(value.to_string()),
-            "Coalition" => Property::Coalition(value.to_string()),
-            "Color" => Property::Color(Color::from(value)),
-            "Shape" => Property::Shape(value.to_string()),
-            "Debug" => Property::Debug(value.to_string()),
-            "Label" => Property::Label(value.to_string()),
-            "FocusedTarget" => Property::FocusedTarget(u64::from_str_radix(value, 16)?),
-            "LockedTarget" => Property::LockedTarget(u64::from_str_radix(value, 16)?),
-            "Importance" => Property::Importance(FromStr::from_str(value)?),
-            "Slot" => Property::Slot(FromStr::from_str(value)?),
-            "Disabled" => Property::Disabled(i64::from_str(value)? != 0),
-            "Visible" => Property::Visible(i64::from_str(value)? != 0),
-            "Health" => Property::Health(FromStr::from_str(value)?),
-            "Length" => Property::Length(FromStr::from_str(value)?),
-            "Width" => Property::Width(FromStr::from_str(value)?),
-            "Height" => Property::Height(FromStr::from_str(value)?),
-            "Radius" => Property::Radius(FromStr::from_str(value)?),
-            "IAS" => Property::IAS(FromStr::from_str(value)?),
-            "CAS" => Property::CAS(FromStr::from_str(value)?),
-            "TAS" => Property::TAS(FromStr::from_str(value)?),
-            "Mach" => Property::Mach(FromStr::from_str(value)?),
-            "AOA" => Property::AOA(FromStr::from_str(value)?),
-            "AOS" => Property::AOS(FromStr::from_str(value)?),
-            "AGL" => Property::AGL(FromStr::from_str(value)?),
-            "HDG" => Property::HDG(FromStr::from_str(value)?),
-            "HDM" => Property::HDM(FromStr::from_str(value)?),
-            "Throttle" => Property::Throttle(FromStr::from_str(value)?),
-            "Throttle2" => Property::Throttle2(FromStr::from_str(value)?),
-            "Afterburner" => Property::Afterburner(FromStr::from_str(value)?),
-            "AirBrakes" => Property::AirBrakes(FromStr::from_str(value)?),
-            "Flaps" => Property::Flaps(FromStr::from_str(value)?),
-            "LandingGear" => Property::LandingGear(FromStr::from_str(value)?),
-            "LandingGearHandle" => Property::LandingGearHandle(FromStr::from_str(value)?),
-            "Tailhook" => Property::Tailhook(FromStr::from_str(value)?),
-            "Parachute" => Property::Parachute(FromStr::from_str(value)?),
-            "DragChute" => Property::DragChute(FromStr::from_str(value)?),
-            "FuelWeight" => Property::FuelWeight(0, FromStr::from_str(value)?),
-            "FuelWeight2" => Property::FuelWeight(1, FromStr::from_str(value)?),
-            "FuelWeight3" => Property::FuelWeight(2, FromStr::from_str(value)?),
-            "FuelWeight4" => Property::FuelWeight(3, FromStr::from_str(value)?),
-            "FuelWeight5" => Property::FuelWeight(4, FromStr::from_str(value)?),
-            "FuelWeight6" => Property::FuelWeight(5, FromStr::from_str(value)?),
-            "FuelWeight7" => Property::FuelWeight(6, FromStr::from_str(value)?),
-            "FuelWeight8" => Property::FuelWeight(7, FromStr::from_str(value)?),
-            "FuelWeight9" => Property::FuelWeight(8, FromStr::from_str(value)?),
-            "FuelVolume" => Property::FuelVolume(0, FromStr::from_str(value)?),
-            "FuelVolume1" => Property::FuelVolume(1, FromStr::from_str(value)?),
-            "FuelVolume2" => Property::FuelVolume(2, FromStr::from_str(value)?),
-            "FuelVolume3" => Property::FuelVolume(3, FromStr::from_str(value)?),
-            "FuelVolume4" => Property::FuelVolume(4, FromStr::from_str(value)?),
-            "FuelVolume5" => Property::FuelVolume(5, FromStr::from_str(value)?),
-            "FuelVolume6" => Property::FuelVolume(6, FromStr::from_str(value)?),
-            "FuelVolume7" => Property::FuelVolume(7, FromStr::from_str(value)?),
-            "FuelVolume8" => Property::FuelVolume(8, FromStr::from_str(value)?),
-            "FuelVolume9" => Property::FuelVolume(9, FromStr::from_str(value)?),
-            "FuelFlowWeight" => Property::FuelFlowWeight(0, FromStr::from_str(value)?),
-            "FuelFlowWeight2" => Property::FuelFlowWeight(1, FromStr::from_str(value)?),
-            "FuelFlowWeight3" => Property::FuelFlowWeight(2, FromStr::from_str(value)?),
-            "FuelFlowWeight4" => Property::FuelFlowWeight(3, FromStr::from_str(value)?),
-            "FuelFlowWeight5" => Property::FuelFlowWeight(4, FromStr::from_str(value)?),
-            "FuelFlowWeight6" => Property::FuelFlowWeight(5, FromStr::from_str(value)?),
-            "FuelFlowWeight7" => Property::FuelFlowWeight(6, FromStr::from_str(value)?),
-            "FuelFlowWeight8" => Property::FuelFlowWeight(7, FromStr::from_str(value)?),
-            "FuelFlowVolume" => Property::FuelFlowVolume(0, FromStr::from_str(value)?),
-            "FuelFlowVolume2" => Property::FuelFlowVolume(1, FromStr::from_str(value)?),
-            "FuelFlowVolume3" => Property::FuelFlowVolume(2, FromStr::from_str(value)?),
-            "FuelFlowVolume4" => Property::FuelFlowVolume(3, FromStr::from_str(value)?),
-            "FuelFlowVolume5" => Property::FuelFlowVolume(4, FromStr::from_str(value)?),
-            "FuelFlowVolume6" => Property::FuelFlowVolume(5, FromStr::from_str(value)?),
-            "FuelFlowVolume7" => Property::FuelFlowVolume(6, FromStr::from_str(value)?),
-            "FuelFlowVolume8" => Property::FuelFlowVolume(7, FromStr::from_str(value)?),
-            "RadarMode" => Property::RadarMode(FromStr::from_str(value)?),
-            "RadarAzimuth" => Property::RadarAzimuth(FromStr::from_str(value)?),
-            "RadarElevation" => Property::RadarElevation(FromStr::from_str(value)?),
-            "RadarRoll" => Property::RadarRoll(FromStr::from_str(value)?),
-            "RadarRange" => Property::RadarRange(FromStr::from_str(value)?),
-            "RadarHorizontalBeamwidth" => {
-                Property::RadarHorizontalBeamwidth(FromStr::from_str(value)?)
-            }
-            "RadarVerticalBeamwidth" => Property::RadarVerticalBeamwidth(FromStr::from_str(value)?),
-            "LockedTargetMode" => Property::LockedTargetMode(FromStr::from_str(value)?),
-            "LockedTargetAzimuth" => Property::LockedTargetAzimuth(FromStr::from_str(value)?),
-            "LockedTargetElevation" => Property::LockedTargetElevation(FromStr::from_str(value)?),
-            "LockedTargetRange" => Property::LockedTargetRange(FromStr::from_str(value)?),
-            "EngagementMode" => Property::EngagementMode(FromStr::from_str(value)?),
-            "EngagementMode2" => Property::EngagementMode2(FromStr::from_str(value)?),
-            "EngagementRange" => Property::EngagementRange(FromStr::from_str(value)?),
-            "EngagementRange2" => Property::EngagementRange2(FromStr::from_str(value)?),
-            "VerticalEngagementRange" => {
-                Property::VerticalEngagementRange(FromStr::from_str(value)?)
-            }
-            "VerticalEngagementRange2" => {
-                Property::VerticalEngagementRange2(FromStr::from_str(value)?)
+            "Name" => Property::Name(unescape(value).into_owned()),
+            "Type" => Property::Type(
+                value
+                    .split('+')
+                    .filter(|tag| !tag.is_empty())
+                    .map(|tag| Tag::from(unescape(tag).as_ref()))
+                    .collect(),
+            ),
+            "Parent" => Property::Parent(id(value)?),
+            "Next" => Property::Next(id(value)?),
+            "CallSign" => Property::CallSign(unescape(value).into_owned()),
+            "Registration" => Property::Registration(unescape(value).into_owned()),
+            "Squawk" => Property::Squawk(unescape(value).into_owned()),
+            "ICAO24" => Property::ICAO24(unescape(value).into_owned()),
+            "Pilot" => Property::Pilot(unescape(value).into_owned()),
+            "Group" => Property::Group(unescape(value).into_owned()),
+            "Country" => Property::Country(Country::from(unescape(value).as_ref())),
+            "Coalition" => Property::Coalition(Coalition::from(unescape(value).as_ref())),
+            "Color" => Property::Color(Color::from(unescape(value).as_ref())),
+            "Shape" => Property::Shape(unescape(value).into_owned()),
+            "Debug" => Property::Debug(unescape(value).into_owned()),
+            "Label" => Property::Label(unescape(value).into_owned()),
+            "FocusedTarget" => Property::FocusedTarget(id(value)?),
+            "LockedTarget" => Property::LockedTarget(id(value)?),
+            "Importance" => Property::Importance(num(value)?),
+            "Slot" => Property::Slot(int(value)?),
+            "Disabled" => Property::Disabled(flag(value)?),
+            "Visible" => Property::Visible(flag(value)?),
+            "Health" => Property::Health(num(value)?),
+            "Length" => Property::Length(num(value)?),
+            "Width" => Property::Width(num(value)?),
+            "Height" => Property::Height(num(value)?),
+            "Radius" => Property::Radius(num(value)?),
+            "IAS" => Property::IAS(num(value)?),
+            "CAS" => Property::CAS(num(value)?),
+            "TAS" => Property::TAS(num(value)?),
+            "Mach" => Property::Mach(num(value)?),
+            "AOA" => Property::AOA(num(value)?),
+            "AOS" => Property::AOS(num(value)?),
+            "AGL" => Property::AGL(num(value)?),
+            "HDG" => Property::HDG(num(value)?),
+            "HDM" => Property::HDM(num(value)?),
+            "Throttle" => Property::Throttle(num(value)?),
+            "Throttle2" => Property::Throttle2(num(value)?),
+            "Afterburner" => Property::Afterburner(num(value)?),
+            "AirBrakes" => Property::AirBrakes(num(value)?),
+            "Flaps" => Property::Flaps(num(value)?),
+            "LandingGear" => Property::LandingGear(num(value)?),
+            "LandingGearHandle" => Property::LandingGearHandle(num(value)?),
+            "Tailhook" => Property::Tailhook(num(value)?),
+            "Parachute" => Property::Parachute(num(value)?),
+            "DragChute" => Property::DragChute(num(value)?),
+            "RadarMode" => Property::RadarMode(num(value)?),
+            "RadarAzimuth" => Property::RadarAzimuth(num(value)?),
+            "RadarElevation" => Property::RadarElevation(num(value)?),
+            "RadarRoll" => Property::RadarRoll(num(value)?),
+            "RadarRange" => Property::RadarRange(num(value)?),
+            "RadarHorizontalBeamwidth" => Property::RadarHorizontalBeamwidth(num(value)?),
+            "RadarVerticalBeamwidth" => Property::RadarVerticalBeamwidth(num(value)?),
+            "LockedTargetMode" => Property::LockedTargetMode(num(value)?),
+            "LockedTargetAzimuth" => Property::LockedTargetAzimuth(num(value)?),
+            "LockedTargetElevation" => Property::LockedTargetElevation(num(value)?),
+            "LockedTargetRange" => Property::LockedTargetRange(num(value)?),
+            "EngagementMode" => Property::EngagementMode(num(value)?),
+            "EngagementMode2" => Property::EngagementMode2(num(value)?),
+            "EngagementRange" => Property::EngagementRange(num(value)?),
+            "EngagementRange2" => Property::EngagementRange2(num(value)?),
+            "VerticalEngagementRange" => Property::VerticalEngagementRange(num(value)?),
+            "VerticalEngagementRange2" => Property::VerticalEngagementRange2(num(value)?),
+            "RollControlInput" => Property::RollControlInput(num(value)?),
+            "PitchControlInput" => Property::PitchControlInput(num(value)?),
+            "YawControlInput" => Property::YawControlInput(num(value)?),
+            "RollControlPosition" => Property::RollControlPosition(num(value)?),
+            "PitchControlPosition" => Property::PitchControlPosition(num(value)?),
+            "YawControlPosition" => Property::YawControlPosition(num(value)?),
+            "RollTrimTab" => Property::RollTrimTab(num(value)?),
+            "PitchTrimTab" => Property::PitchTrimTab(num(value)?),
+            "YawTrimTab" => Property::YawTrimTab(num(value)?),
+            "AileronLeft" => Property::AileronLeft(num(value)?),
+            "AileronRight" => Property::AileronRight(num(value)?),
+            "Elevator" => Property::Elevator(num(value)?),
+            "Rudder" => Property::Rudder(num(value)?),
+            "PilotHeadRoll" => Property::PilotHeadRoll(num(value)?),
+            "PilotHeadPitch" => Property::PilotHeadPitch(num(value)?),
+            "PilotHeadYaw" => Property::PilotHeadYaw(num(value)?),
+            "VerticalGForce" => Property::VerticalGForce(num(value)?),
+            "LongitudinalGForce" => Property::LongitudinalGForce(num(value)?),
+            "LateralGForce" => Property::LateralGForce(num(value)?),
+            "ENL" => Property::ENL(num(value)?),
+            name => {
+                if let Some(i) = indexed(name, "FuelWeight") {
+                    Property::FuelWeight(i, num(value)?)
+                } else if let Some(i) = indexed(name, "FuelVolume") {
+                    Property::FuelVolume(i, num(value)?)
+                } else if let Some(i) = indexed(name, "FuelFlowWeight") {
+                    Property::FuelFlowWeight(i, num(value)?)
+                } else if let Some(i) = indexed(name, "FuelFlowVolume") {
+                    Property::FuelFlowVolume(i, num(value)?)
+                } else {
+                    Self::Unknown(name.to_string(), unescape(value).into_owned())
+                }
             }
-            "RollControlInput" => Property::RollControlInput(FromStr::from_str(value)?),
-            "PitchControlInput" => Property::PitchControlInput(FromStr::from_str(value)?),
-            "YawControlInput" => Property::YawControlInput(FromStr::from_str(value)?),
-            "RollControlPosition" => Property::RollControlPosition(FromStr::from_str(value)?),
-            "PitchControlPosition" => Property::PitchControlPosition(FromStr::from_str(value)?),
-            "YawControlPosition" => Property::YawControlPosition(FromStr::from_str(value)?),
-            "RollTrimTab" => Property::RollTrimTab(FromStr::from_str(value)?),
-            "PitchTrimTab" => Property::PitchTrimTab(FromStr::from_str(value)?),
-            "YawTrimTab" => Property::YawTrimTab(FromStr::from_str(value)?),
-            "AileronLeft" => Property::AileronLeft(FromStr::from_str(value)?),
-            "AileronRight" => Property::AileronRight(FromStr::from_str(value)?),
-            "Elevator" => Property::Elevator(FromStr::from_str(value)?),
-            "Rudder" => Property::Rudder(FromStr::from_str(value)?),
-            "PilotHeadRoll" => Property::PilotHeadRoll(FromStr::from_str(value)?),
-            "PilotHeadPitch" => Property::PilotHeadPitch(FromStr::from_str(value)?),
-            "PilotHeadYaw" => Property::PilotHeadYaw(FromStr::from_str(value)?),
-            "VerticalGForce" => Property::VerticalGForce(FromStr::from_str(value)?),
-            "LongitudinalGForce" => Property::LongitudinalGForce(FromStr::from_str(value)?),
-            "LateralGForce" => Property::LateralGForce(FromStr::from_str(value)?),
-            "ENL" => Property::ENL(FromStr::from_str(value)?),
-            name => Self::Unknown(name.to_string(), value.to_string()),
         })
     }
 }
 
 impl Display for Property {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         use Property::*;
         match self {
             T(v) => write!(f, "T={v}"),
-            Name(v) => write!(f, "Name={v}"),
-            Type(v) => write!(f, "Type={}", join(v.iter().map(|v| v.as_str()), "+")),
+            Name(v) => write!(f, "Name={}", Escaped(v)),
+            Type(v) => write!(f, "Type={}", join(v.iter(), "+")),
             Parent(v) => write!(f, "Parent={v:x}"),
             Next(v) => write!(f, "Next={v:x}"),
-            CallSign(v) => write!(f, "CallSign={v}"),
-            Registration(v) => write!(f, "Registration={v}"),
-            Squawk(v) => write!(f, "Squawk={v}"),
-            ICAO24(v) => write!(f, "ICAO24={v}"),
-            Pilot(v) => write!(f, "Pilot={v}"),
-            Group(v) => write!(f, "Group={v}"),
+            CallSign(v) => write!(f, "CallSign={}", Escaped(v)),
+            Registration(v) => write!(f, "Registration={}", Escaped(v)),
+            Squawk(v) => write!(f, "Squawk={}", Escaped(v)),
+            ICAO24(v) => write!(f, "ICAO24={}", Escaped(v)),
+            Pilot(v) => write!(f, "Pilot={}", Escaped(v)),
+            Group(v) => write!(f, "Group={}", Escaped(v)),
             Country(v) => write!(f, "Country={v}"),
             Coalition(v) => write!(f, "Coalition={v}"),
-            Color(v) => write!(f, "Color={}", v.as_str()),
-            Shape(v) => write!(f, "Shape={v}"),
-            Debug(v) => write!(f, "Debug={v}"),
-            Label(v) => write!(f, "Label={v}"),
+            Color(v) => write!(f, "Color={v}"),
+            Shape(v) => write!(f, "Shape={}", Escaped(v)),
+            Debug(v) => write!(f, "Debug={}", Escaped(v)),
+            Label(v) => write!(f, "Label={}", Escaped(v)),
             FocusedTarget(v) => write!(f, "FocusedTarget={v:x}"),
             LockedTarget(v) => write!(f, "LockedTarget={v:x}"),
             Importance(v) => write!(f, "Importance={v}"),
@@ -757,40 +1009,694 @@ impl Display for Property {
             LongitudinalGForce(v) => write!(f, "LongitudinalGForce={v}"),
             LateralGForce(v) => write!(f, "LateralGForce={v}"),
             ENL(v) => write!(f, "ENL={v}"),
-            Unknown(k, v) => write!(f, "{k}={v}"),
+            Unknown(k, v) => write!(f, "{k}={}", Escaped(v)),
+        }
+    }
+}
+
+impl Property {
+    /// The kind of the property, e.g. to filter properties or to use as a map key.
+    pub fn kind(&self) -> PropertyKind {
+        use Property::*;
+        match self {
+            T(..) => PropertyKind::T,
+            Name(..) => PropertyKind::Name,
+            Type(..) => PropertyKind::Type,
+            Parent(..) => PropertyKind::Parent,
+            Next(..) => PropertyKind::Next,
+            CallSign(..) => PropertyKind::CallSign,
+            Registration(..) => PropertyKind::Registration,
+            Squawk(..) => PropertyKind::Squawk,
+            ICAO24(..) => PropertyKind::ICAO24,
+            Pilot(..) => PropertyKind::Pilot,
+            Group(..) => PropertyKind::Group,
+            Country(..) => PropertyKind::Country,
+            Coalition(..) => PropertyKind::Coalition,
+            Color(..) => PropertyKind::Color,
+            Shape(..) => PropertyKind::Shape,
+            Debug(..) => PropertyKind::Debug,
+            Label(..) => PropertyKind::Label,
+            FocusedTarget(..) => PropertyKind::FocusedTarget,
+            LockedTarget(..) => PropertyKind::LockedTarget,
+            Importance(..) => PropertyKind::Importance,
+            Slot(..) => PropertyKind::Slot,
+            Disabled(..) => PropertyKind::Disabled,
+            Visible(..) => PropertyKind::Visible,
+            Health(..) => PropertyKind::Health,
+            Length(..) => PropertyKind::Length,
+            Width(..) => PropertyKind::Width,
+            Height(..) => PropertyKind::Height,
+            Radius(..) => PropertyKind::Radius,
+            IAS(..) => PropertyKind::IAS,
+            CAS(..) => PropertyKind::CAS,
+            TAS(..) => PropertyKind::TAS,
+            Mach(..) => PropertyKind::Mach,
+            AOA(..) => PropertyKind::AOA,
+            AOS(..) => PropertyKind::AOS,
+            AGL(..) => PropertyKind::AGL,
+            HDG(..) => PropertyKind::HDG,
+            HDM(..) => PropertyKind::HDM,
+            Throttle(..) => PropertyKind::Throttle,
+            Throttle2(..) => PropertyKind::Throttle2,
+            Afterburner(..) => PropertyKind::Afterburner,
+            AirBrakes(..) => PropertyKind::AirBrakes,
+            Flaps(..) => PropertyKind::Flaps,
+            LandingGear(..) => PropertyKind::LandingGear,
+            LandingGearHandle(..) => PropertyKind::LandingGearHandle,
+            Tailhook(..) => PropertyKind::Tailhook,
+            Parachute(..) => PropertyKind::Parachute,
+            DragChute(..) => PropertyKind::DragChute,
+            FuelWeight(..) => PropertyKind::FuelWeight,
+            FuelVolume(..) => PropertyKind::FuelVolume,
+            FuelFlowWeight(..) => PropertyKind::FuelFlowWeight,
+            FuelFlowVolume(..) => PropertyKind::FuelFlowVolume,
+            RadarMode(..) => PropertyKind::RadarMode,
+            RadarAzimuth(..) => PropertyKind::RadarAzimuth,
+            RadarElevation(..) => PropertyKind::RadarElevation,
+            RadarRoll(..) => PropertyKind::RadarRoll,
+            RadarRange(..) => PropertyKind::RadarRange,
+            RadarHorizontalBeamwidth(..) => PropertyKind::RadarHorizontalBeamwidth,
+            RadarVerticalBeamwidth(..) => PropertyKind::RadarVerticalBeamwidth,
+            LockedTargetMode(..) => PropertyKind::LockedTargetMode,
+            LockedTargetAzimuth(..) => PropertyKind::LockedTargetAzimuth,
+            LockedTargetElevation(..) => PropertyKind::LockedTargetElevation,
+            LockedTargetRange(..) => PropertyKind::LockedTargetRange,
+            EngagementMode(..) => PropertyKind::EngagementMode,
+            EngagementMode2(..) => PropertyKind::EngagementMode2,
+            EngagementRange(..) => PropertyKind::EngagementRange,
+            EngagementRange2(..) => PropertyKind::EngagementRange2,
+            VerticalEngagementRange(..) => PropertyKind::VerticalEngagementRange,
+            VerticalEngagementRange2(..) => PropertyKind::VerticalEngagementRange2,
+            RollControlInput(..) => PropertyKind::RollControlInput,
+            PitchControlInput(..) => PropertyKind::PitchControlInput,
+            YawControlInput(..) => PropertyKind::YawControlInput,
+            RollControlPosition(..) => PropertyKind::RollControlPosition,
+            PitchControlPosition(..) => PropertyKind::PitchControlPosition,
+            YawControlPosition(..) => PropertyKind::YawControlPosition,
+            RollTrimTab(..) => PropertyKind::RollTrimTab,
+            PitchTrimTab(..) => PropertyKind::PitchTrimTab,
+            YawTrimTab(..) => PropertyKind::YawTrimTab,
+            AileronLeft(..) => PropertyKind::AileronLeft,
+            AileronRight(..) => PropertyKind::AileronRight,
+            Elevator(..) => PropertyKind::Elevator,
+            Rudder(..) => PropertyKind::Rudder,
+            PilotHeadRoll(..) => PropertyKind::PilotHeadRoll,
+            PilotHeadPitch(..) => PropertyKind::PilotHeadPitch,
+            PilotHeadYaw(..) => PropertyKind::PilotHeadYaw,
+            VerticalGForce(..) => PropertyKind::VerticalGForce,
+            LongitudinalGForce(..) => PropertyKind::LongitudinalGForce,
+            LateralGForce(..) => PropertyKind::LateralGForce,
+            ENL(..) => PropertyKind::ENL,
+            Unknown(..) => PropertyKind::Unknown,
+        }
+    }
+
+    /// The unit of the property's value, see [`PropertyKind::unit`].
+    pub fn unit(&self) -> Option<&'static str> {
+        self.kind().unit()
+    }
+
+    /// Whether both properties set the same value, i.e. would be written with the same name.
+    #[cfg(feature = "std")]
+    pub(crate) fn same_key(&self, other: &Property) -> bool {
+        use Property::*;
+        match (self, other) {
+            (FuelWeight(a, _), FuelWeight(b, _))
+            | (FuelVolume(a, _), FuelVolume(b, _))
+            | (FuelFlowWeight(a, _), FuelFlowWeight(b, _))
+            | (FuelFlowVolume(a, _), FuelFlowVolume(b, _)) => a == b,
+            (Unknown(a, _), Unknown(b, _)) => a == b,
+            (a, b) => core::mem::discriminant(a) == core::mem::discriminant(b),
+        }
+    }
+
+    /// The name and value of the property as they are written to an ACMI file, e.g.
+    /// `("FuelWeight2", "100")`. Text values are escaped.
+    pub fn to_kv(&self) -> (String, String) {
+        let kv = self.to_string();
+        match kv.split_once('=') {
+            Some((name, value)) => (name.to_string(), value.to_string()),
+            None => (kv, String::new()),
+        }
+    }
+
+    /// Whether this is a [`Property::Squawk`] with a valid Mode 3/A code, i.e. four octal digits.
+    /// Other codes are still accepted when parsing, as ACMI does not limit transponder codes.
+    pub fn squawk_is_valid_octal(&self) -> bool {
+        match self {
+            Property::Squawk(code) => {
+                code.len() == 4 && code.bytes().all(|b| matches!(b, b'0'..=b'7'))
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether this is a [`Property::ICAO24`] with a valid Mode S address, i.e. six hex digits.
+    pub fn icao24_is_valid(&self) -> bool {
+        match self {
+            Property::ICAO24(address) => {
+                address.len() == 6 && address.bytes().all(|b| b.is_ascii_hexdigit())
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether this is a [`Property::Type`] containing `tag`. Always `false` for other properties.
+    pub fn has_tag(&self, tag: &Tag) -> bool {
+        match self {
+            Property::Type(tags) => tags.contains(tag),
+            _ => false,
+        }
+    }
+
+    /// Whether this is a [`Property::Type`] of the `Air` class.
+    pub fn is_air(&self) -> bool {
+        self.has_tag(&Tag::Air)
+    }
+
+    /// Whether this is a [`Property::Type`] of the `Ground` class.
+    pub fn is_ground(&self) -> bool {
+        self.has_tag(&Tag::Ground)
+    }
+
+    /// Whether this is a [`Property::Type`] of the `Sea` class.
+    pub fn is_sea(&self) -> bool {
+        self.has_tag(&Tag::Sea)
+    }
+
+    /// Whether this is a [`Property::Type`] of the `Weapon` class.
+    pub fn is_weapon(&self) -> bool {
+        self.has_tag(&Tag::Weapon)
+    }
+
+    /// The state of mode properties ([`Property::RadarMode`], [`Property::LockedTargetMode`],
+    /// [`Property::EngagementMode`] and [`Property::EngagementMode2`]), `None` for all other
+    /// properties.
+    pub fn mode(&self) -> Option<Mode> {
+        use Property::*;
+        match self {
+            RadarMode(v) | LockedTargetMode(v) | EngagementMode(v) | EngagementMode2(v) => {
+                Some(Mode::from(*v))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether this is a [`Property::RadarMode`] of a radar that is on (i.e. emitting).
+    pub fn is_radar_on(&self) -> bool {
+        matches!(self, Property::RadarMode(v) if Mode::from(*v).is_on())
+    }
+
+    /// A mutable reference to the numeric value of properties that carry one.
+    fn numeric_mut(&mut self) -> Option<&mut f64> {
+        use Property::*;
+        match self {
+            Importance(v)
+            | Health(v)
+            | Length(v)
+            | Width(v)
+            | Height(v)
+            | Radius(v)
+            | IAS(v)
+            | CAS(v)
+            | TAS(v)
+            | Mach(v)
+            | AOA(v)
+            | AOS(v)
+            | AGL(v)
+            | HDG(v)
+            | HDM(v)
+            | Throttle(v)
+            | Throttle2(v)
+            | Afterburner(v)
+            | AirBrakes(v)
+            | Flaps(v)
+            | LandingGear(v)
+            | LandingGearHandle(v)
+            | Tailhook(v)
+            | Parachute(v)
+            | DragChute(v)
+            | RadarMode(v)
+            | RadarAzimuth(v)
+            | RadarElevation(v)
+            | RadarRoll(v)
+            | RadarRange(v)
+            | RadarHorizontalBeamwidth(v)
+            | RadarVerticalBeamwidth(v)
+            | LockedTargetMode(v)
+            | LockedTargetAzimuth(v)
+            | LockedTargetElevation(v)
+            | LockedTargetRange(v)
+            | EngagementMode(v)
+            | EngagementMode2(v)
+            | EngagementRange(v)
+            | EngagementRange2(v)
+            | VerticalEngagementRange(v)
+            | VerticalEngagementRange2(v)
+            | RollControlInput(v)
+            | PitchControlInput(v)
+            | YawControlInput(v)
+            | RollControlPosition(v)
+            | PitchControlPosition(v)
+            | YawControlPosition(v)
+            | RollTrimTab(v)
+            | PitchTrimTab(v)
+            | YawTrimTab(v)
+            | AileronLeft(v)
+            | AileronRight(v)
+            | Elevator(v)
+            | Rudder(v)
+            | PilotHeadRoll(v)
+            | PilotHeadPitch(v)
+            | PilotHeadYaw(v)
+            | VerticalGForce(v)
+            | LongitudinalGForce(v)
+            | LateralGForce(v)
+            | ENL(v)
+            | FuelWeight(_, v)
+            | FuelVolume(_, v)
+            | FuelFlowWeight(_, v)
+            | FuelFlowVolume(_, v) => Some(v),
+            _ => None,
         }
     }
 }
 
-impl<'a> From<&'a str> for Color {
+impl Precision for Property {
+    fn max_precision(mut self, max_precision: u32) -> Self {
+        if let Property::T(coords) = self {
+            return Property::T(coords.max_precision(max_precision));
+        }
+        if let Some(v) = self.numeric_mut() {
+            *v = v.max_precision(max_precision);
+        }
+        self
+    }
+}
+
+impl Precision for Coords {
+    fn max_precision(self, max_precision: u32) -> Self {
+        Coords {
+            longitude: self.longitude.max_precision(max_precision),
+            latitude: self.latitude.max_precision(max_precision),
+            altitude: self.altitude.max_precision(max_precision),
+            u: self.u.max_precision(max_precision),
+            v: self.v.max_precision(max_precision),
+            roll: self.roll.max_precision(max_precision),
+            pitch: self.pitch.max_precision(max_precision),
+            yaw: self.yaw.max_precision(max_precision),
+            heading: self.heading.max_precision(max_precision),
+        }
+    }
+}
+
+impl From<&str> for Color {
     fn from(s: &str) -> Self {
         match s {
             "Red" => Self::Red,
             "Orange" => Self::Orange,
+            "Yellow" => Self::Yellow,
             "Green" => Self::Green,
+            "Cyan" => Self::Cyan,
             "Blue" => Self::Blue,
             "Violet" => Self::Violet,
-            color => Self::Unknown(color.to_string()),
+            "Grey" => Self::Grey,
+            "White" => Self::White,
+            color => parse_hex_color(color).unwrap_or_else(|| Self::Unknown(color.to_string())),
         }
     }
 }
 
+/// The RGB values of the named colors, see [`Color::to_rgb`].
+const NAMED_COLORS: [(Color, [u8; 3]); 9] = [
+    (Color::Red, [255, 0, 0]),
+    (Color::Orange, [255, 128, 0]),
+    (Color::Yellow, [255, 255, 0]),
+    (Color::Green, [0, 255, 0]),
+    (Color::Cyan, [0, 255, 255]),
+    (Color::Blue, [0, 0, 255]),
+    (Color::Violet, [128, 0, 255]),
+    (Color::Grey, [128, 128, 128]),
+    (Color::White, [255, 255, 255]),
+];
+
 impl Color {
-    fn as_str(&self) -> &str {
+    /// The RGB value of the color, `None` for [`Color::Unknown`]. Tacview does not document the
+    /// palette it renders named colors with, so named colors map to their plain RGB counterparts
+    /// (e.g. `Orange` is `[255, 128, 0]`).
+    pub fn to_rgb(&self) -> Option<[u8; 3]> {
+        match self {
+            Color::Rgb(r, g, b) => Some([*r, *g, *b]),
+            Color::Unknown(_) => None,
+            named => NAMED_COLORS
+                .iter()
+                .find(|(color, _)| color == named)
+                .map(|(_, rgb)| *rgb),
+        }
+    }
+}
+
+impl From<[u8; 3]> for Color {
+    /// The named color with exactly this RGB value (see [`Color::to_rgb`]), or [`Color::Rgb`].
+    fn from([r, g, b]: [u8; 3]) -> Self {
+        NAMED_COLORS
+            .into_iter()
+            .find(|(_, rgb)| *rgb == [r, g, b])
+            .map(|(color, _)| color)
+            .unwrap_or(Color::Rgb(r, g, b))
+    }
+}
+
+fn parse_hex_color(s: &str) -> Option<Color> {
+    let hex = s.strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize, len: usize| u8::from_str_radix(&hex[i * len..(i + 1) * len], 16).ok();
+    match hex.len() {
+        6 => Some(Color::Rgb(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?)),
+        3 => Some(Color::Rgb(
+            channel(0, 1)? * 0x11,
+            channel(1, 1)? * 0x11,
+            channel(2, 1)? * 0x11,
+        )),
+        _ => None,
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         use Color::*;
         match self {
-            Red => "Red",
-            Orange => "Orange",
-            Green => "Green",
-            Blue => "Blue",
-            Violet => "Violet",
-            Grey => "Grey",
-            Unknown(color) => color,
+            Red => f.write_str("Red"),
+            Orange => f.write_str("Orange"),
+            Yellow => f.write_str("Yellow"),
+            Green => f.write_str("Green"),
+            Cyan => f.write_str("Cyan"),
+            Blue => f.write_str("Blue"),
+            Violet => f.write_str("Violet"),
+            Grey => f.write_str("Grey"),
+            White => f.write_str("White"),
+            Rgb(r, g, b) => write!(f, "#{r:02X}{g:02X}{b:02X}"),
+            Unknown(color) => write!(f, "{}", Escaped(color)),
+        }
+    }
+}
+
+impl From<&str> for Coalition {
+    fn from(s: &str) -> Self {
+        match s {
+            "Allies" => Self::Allies,
+            "Enemies" => Self::Enemies,
+            "Neutrals" => Self::Neutrals,
+            coalition => Self::Unknown(coalition.to_string()),
+        }
+    }
+}
+
+impl Display for Coalition {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use Coalition::*;
+        match self {
+            Allies => f.write_str("Allies"),
+            Enemies => f.write_str("Enemies"),
+            Neutrals => f.write_str("Neutrals"),
+            Unknown(coalition) => write!(f, "{}", Escaped(coalition)),
+        }
+    }
+}
+
+impl From<&Property> for PropertyKind {
+    fn from(p: &Property) -> Self {
+        p.kind()
+    }
+}
+
+impl From<&str> for PropertyKind {
+    /// The kind of a property by its name as written to an ACMI file, e.g. `FuelWeight2` is a
+    /// [`PropertyKind::FuelWeight`]. Does not parse the property's value.
+    fn from(name: &str) -> Self {
+        use PropertyKind::*;
+        match name {
+            "T" => T,
+            "Name" => Name,
+            "Type" => Type,
+            "Parent" => Parent,
+            "Next" => Next,
+            "CallSign" => CallSign,
+            "Registration" => Registration,
+            "Squawk" => Squawk,
+            "ICAO24" => ICAO24,
+            "Pilot" => Pilot,
+            "Group" => Group,
+            "Country" => Country,
+            "Coalition" => Coalition,
+            "Color" => Color,
+            "Shape" => Shape,
+            "Debug" => Debug,
+            "Label" => Label,
+            "FocusedTarget" => FocusedTarget,
+            "LockedTarget" => LockedTarget,
+            "Importance" => Importance,
+            "Slot" => Slot,
+            "Disabled" => Disabled,
+            "Visible" => Visible,
+            "Health" => Health,
+            "Length" => Length,
+            "Width" => Width,
+            "Height" => Height,
+            "Radius" => Radius,
+            "IAS" => IAS,
+            "CAS" => CAS,
+            "TAS" => TAS,
+            "Mach" => Mach,
+            "AOA" => AOA,
+            "AOS" => AOS,
+            "AGL" => AGL,
+            "HDG" => HDG,
+            "HDM" => HDM,
+            "Throttle" => Throttle,
+            "Throttle2" => Throttle2,
+            "Afterburner" => Afterburner,
+            "AirBrakes" => AirBrakes,
+            "Flaps" => Flaps,
+            "LandingGear" => LandingGear,
+            "LandingGearHandle" => LandingGearHandle,
+            "Tailhook" => Tailhook,
+            "Parachute" => Parachute,
+            "DragChute" => DragChute,
+            "RadarMode" => RadarMode,
+            "RadarAzimuth" => RadarAzimuth,
+            "RadarElevation" => RadarElevation,
+            "RadarRoll" => RadarRoll,
+            "RadarRange" => RadarRange,
+            "RadarHorizontalBeamwidth" => RadarHorizontalBeamwidth,
+            "RadarVerticalBeamwidth" => RadarVerticalBeamwidth,
+            "LockedTargetMode" => LockedTargetMode,
+            "LockedTargetAzimuth" => LockedTargetAzimuth,
+            "LockedTargetElevation" => LockedTargetElevation,
+            "LockedTargetRange" => LockedTargetRange,
+            "EngagementMode" => EngagementMode,
+            "EngagementMode2" => EngagementMode2,
+            "EngagementRange" => EngagementRange,
+            "EngagementRange2" => EngagementRange2,
+            "VerticalEngagementRange" => VerticalEngagementRange,
+            "VerticalEngagementRange2" => VerticalEngagementRange2,
+            "RollControlInput" => RollControlInput,
+            "PitchControlInput" => PitchControlInput,
+            "YawControlInput" => YawControlInput,
+            "RollControlPosition" => RollControlPosition,
+            "PitchControlPosition" => PitchControlPosition,
+            "YawControlPosition" => YawControlPosition,
+            "RollTrimTab" => RollTrimTab,
+            "PitchTrimTab" => PitchTrimTab,
+            "YawTrimTab" => YawTrimTab,
+            "AileronLeft" => AileronLeft,
+            "AileronRight" => AileronRight,
+            "Elevator" => Elevator,
+            "Rudder" => Rudder,
+            "PilotHeadRoll" => PilotHeadRoll,
+            "PilotHeadPitch" => PilotHeadPitch,
+            "PilotHeadYaw" => PilotHeadYaw,
+            "VerticalGForce" => VerticalGForce,
+            "LongitudinalGForce" => LongitudinalGForce,
+            "LateralGForce" => LateralGForce,
+            "ENL" => ENL,
+            name if indexed(name, "FuelWeight").is_some() => FuelWeight,
+            name if indexed(name, "FuelVolume").is_some() => FuelVolume,
+            name if indexed(name, "FuelFlowWeight").is_some() => FuelFlowWeight,
+            name if indexed(name, "FuelFlowVolume").is_some() => FuelFlowVolume,
+            _ => Unknown,
+        }
+    }
+}
+
+impl PropertyKind {
+    /// The name of the property (without index for indexed properties), or `Unknown`.
+    pub fn name(&self) -> &'static str {
+        use PropertyKind::*;
+        match self {
+            T => "T",
+            Name => "Name",
+            Type => "Type",
+            Parent => "Parent",
+            Next => "Next",
+            CallSign => "CallSign",
+            Registration => "Registration",
+            Squawk => "Squawk",
+            ICAO24 => "ICAO24",
+            Pilot => "Pilot",
+            Group => "Group",
+            Country => "Country",
+            Coalition => "Coalition",
+            Color => "Color",
+            Shape => "Shape",
+            Debug => "Debug",
+            Label => "Label",
+            FocusedTarget => "FocusedTarget",
+            LockedTarget => "LockedTarget",
+            Importance => "Importance",
+            Slot => "Slot",
+            Disabled => "Disabled",
+            Visible => "Visible",
+            Health => "Health",
+            Length => "Length",
+            Width => "Width",
+            Height => "Height",
+            Radius => "Radius",
+            IAS => "IAS",
+            CAS => "CAS",
+            TAS => "TAS",
+            Mach => "Mach",
+            AOA => "AOA",
+            AOS => "AOS",
+            AGL => "AGL",
+            HDG => "HDG",
+            HDM => "HDM",
+            Throttle => "Throttle",
+            Throttle2 => "Throttle2",
+            Afterburner => "Afterburner",
+            AirBrakes => "AirBrakes",
+            Flaps => "Flaps",
+            LandingGear => "LandingGear",
+            LandingGearHandle => "LandingGearHandle",
+            Tailhook => "Tailhook",
+            Parachute => "Parachute",
+            DragChute => "DragChute",
+            FuelWeight => "FuelWeight",
+            FuelVolume => "FuelVolume",
+            FuelFlowWeight => "FuelFlowWeight",
+            FuelFlowVolume => "FuelFlowVolume",
+            RadarMode => "RadarMode",
+            RadarAzimuth => "RadarAzimuth",
+            RadarElevation => "RadarElevation",
+            RadarRoll => "RadarRoll",
+            RadarRange => "RadarRange",
+            RadarHorizontalBeamwidth => "RadarHorizontalBeamwidth",
+            RadarVerticalBeamwidth => "RadarVerticalBeamwidth",
+            LockedTargetMode => "LockedTargetMode",
+            LockedTargetAzimuth => "LockedTargetAzimuth",
+            LockedTargetElevation => "LockedTargetElevation",
+            LockedTargetRange => "LockedTargetRange",
+            EngagementMode => "EngagementMode",
+            EngagementMode2 => "EngagementMode2",
+            EngagementRange => "EngagementRange",
+            EngagementRange2 => "EngagementRange2",
+            VerticalEngagementRange => "VerticalEngagementRange",
+            VerticalEngagementRange2 => "VerticalEngagementRange2",
+            RollControlInput => "RollControlInput",
+            PitchControlInput => "PitchControlInput",
+            YawControlInput => "YawControlInput",
+            RollControlPosition => "RollControlPosition",
+            PitchControlPosition => "PitchControlPosition",
+            YawControlPosition => "YawControlPosition",
+            RollTrimTab => "RollTrimTab",
+            PitchTrimTab => "PitchTrimTab",
+            YawTrimTab => "YawTrimTab",
+            AileronLeft => "AileronLeft",
+            AileronRight => "AileronRight",
+            Elevator => "Elevator",
+            Rudder => "Rudder",
+            PilotHeadRoll => "PilotHeadRoll",
+            PilotHeadPitch => "PilotHeadPitch",
+            PilotHeadYaw => "PilotHeadYaw",
+            VerticalGForce => "VerticalGForce",
+            LongitudinalGForce => "LongitudinalGForce",
+            LateralGForce => "LateralGForce",
+            ENL => "ENL",
+            Unknown => "Unknown",
+        }
+    }
+
+    /// The unit values of the property are in, as documented for each [`Property`] variant, e.g.
+    /// `"m"` or `"deg"`. Returns `None` for properties without a (single) unit, like names, ids or
+    /// [`PropertyKind::T`], whose components have different units.
+    pub fn unit(&self) -> Option<&'static str> {
+        use PropertyKind::*;
+        match self {
+            Importance | Health | Mach | Throttle | Throttle2 | Afterburner | AirBrakes | Flaps
+            | LandingGear | LandingGearHandle | Tailhook | Parachute | DragChute
+            | RollControlInput | PitchControlInput | YawControlInput | RollControlPosition
+            | PitchControlPosition | YawControlPosition | RollTrimTab | PitchTrimTab
+            | YawTrimTab | AileronLeft | AileronRight | Elevator | Rudder | PilotHeadRoll
+            | PilotHeadPitch | PilotHeadYaw | ENL => Some("ratio"),
+            Length
+            | Width
+            | Height
+            | Radius
+            | AGL
+            | RadarRange
+            | LockedTargetRange
+            | EngagementRange
+            | EngagementRange2
+            | VerticalEngagementRange
+            | VerticalEngagementRange2 => Some("m"),
+            IAS | CAS | TAS => Some("m/s"),
+            AOA
+            | AOS
+            | HDG
+            | HDM
+            | RadarAzimuth
+            | RadarElevation
+            | RadarRoll
+            | RadarHorizontalBeamwidth
+            | RadarVerticalBeamwidth
+            | LockedTargetAzimuth
+            | LockedTargetElevation => Some("deg"),
+            FuelWeight => Some("kg"),
+            FuelVolume => Some("l"),
+            FuelFlowWeight => Some("kg/hour"),
+            FuelFlowVolume => Some("l/hour"),
+            VerticalGForce | LongitudinalGForce | LateralGForce => Some("g"),
+            _ => None,
         }
     }
 }
 
-impl<'a> From<&'a str> for Tag {
+impl Display for PropertyKind {
+    /// See [`PropertyKind::name`].
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl From<f64> for Mode {
+    fn from(v: f64) -> Self {
+        if v == 0.0 {
+            Self::Off
+        } else {
+            Self::On(v)
+        }
+    }
+}
+
+impl Mode {
+    pub fn is_on(&self) -> bool {
+        matches!(self, Mode::On(_))
+    }
+}
+
+impl From<&str> for Tag {
     fn from(s: &str) -> Self {
         match s {
             "Air" => Self::Air,
@@ -831,17 +1737,28 @@ impl<'a> From<&'a str> for Tag {
             "Parachutist" => Self::Parachutist,
             "Shell" => Self::Shell,
             "Bullet" => Self::Bullet,
+            "Grenade" => Self::Grenade,
             "Flare" => Self::Flare,
             "Chaff" => Self::Chaff,
             "SmokeGrenade" => Self::SmokeGrenade,
             "Aerodrome" => Self::Aerodrome,
             "Container" => Self::Container,
             "Shrapnel" => Self::Shrapnel,
+            "Explosion" => Self::Explosion,
             tag => Self::Unknown(tag.to_string()),
         }
     }
 }
 
+impl Display for Tag {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Tag::Unknown(tag) => write!(f, "{}", Escaped(tag)),
+            tag => f.write_str(tag.as_str()),
+        }
+    }
+}
+
 impl Tag {
     fn as_str(&self) -> &str {
         use Tag::*;
@@ -884,12 +1801,14 @@ impl Tag {
             Parachutist => "Parachutist",
             Shell => "Shell",
             Bullet => "Bullet",
+            Grenade => "Grenade",
             Flare => "Flare",
             Chaff => "Chaff",
             SmokeGrenade => "SmokeGrenade",
             Aerodrome => "Aerodrome",
             Container => "Container",
             Shrapnel => "Shrapnel",
+            Explosion => "Explosion",
             Unknown(tag) => tag,
         }
     }
@@ -904,79 +1823,79 @@ impl FromStr for Coords {
         match &parts[..] {
             [longitude, latitude, altitude] => {
                 if !longitude.is_empty() {
-                    coords.longitude = Some(f64::from_str(longitude)?);
+                    coords.longitude = Some(parse_numeric("longitude", longitude)?);
                 }
                 if !latitude.is_empty() {
-                    coords.latitude = Some(f64::from_str(latitude)?);
+                    coords.latitude = Some(parse_numeric("latitude", latitude)?);
                 }
                 if !altitude.is_empty() {
-                    coords.altitude = Some(f64::from_str(altitude)?);
+                    coords.altitude = Some(parse_numeric("altitude", altitude)?);
                 }
             }
             [longitude, latitude, altitude, u, v] => {
                 if !longitude.is_empty() {
-                    coords.longitude = Some(f64::from_str(longitude)?);
+                    coords.longitude = Some(parse_numeric("longitude", longitude)?);
                 }
                 if !latitude.is_empty() {
-                    coords.latitude = Some(f64::from_str(latitude)?);
+                    coords.latitude = Some(parse_numeric("latitude", latitude)?);
                 }
                 if !altitude.is_empty() {
-                    coords.altitude = Some(f64::from_str(altitude)?);
+                    coords.altitude = Some(parse_numeric("altitude", altitude)?);
                 }
                 if !u.is_empty() {
-                    coords.u = Some(f64::from_str(u)?);
+                    coords.u = Some(parse_numeric("u", u)?);
                 }
                 if !v.is_empty() {
-                    coords.v = Some(f64::from_str(v)?);
+                    coords.v = Some(parse_numeric("v", v)?);
                 }
             }
             [longitude, latitude, altitude, roll, pitch, yaw] => {
                 if !longitude.is_empty() {
-                    coords.longitude = Some(f64::from_str(longitude)?);
+                    coords.longitude = Some(parse_numeric("longitude", longitude)?);
                 }
                 if !latitude.is_empty() {
-                    coords.latitude = Some(f64::from_str(latitude)?);
+                    coords.latitude = Some(parse_numeric("latitude", latitude)?);
                 }
                 if !altitude.is_empty() {
-                    coords.altitude = Some(f64::from_str(altitude)?);
+                    coords.altitude = Some(parse_numeric("altitude", altitude)?);
                 }
                 if !roll.is_empty() {
-                    coords.roll = Some(f64::from_str(roll)?);
+                    coords.roll = Some(parse_numeric("roll", roll)?);
                 }
                 if !pitch.is_empty() {
-                    coords.pitch = Some(f64::from_str(pitch)?);
+                    coords.pitch = Some(parse_numeric("pitch", pitch)?);
                 }
                 if !yaw.is_empty() {
-                    coords.yaw = Some(f64::from_str(yaw)?);
+                    coords.yaw = Some(parse_numeric("yaw", yaw)?);
                 }
             }
             [longitude, latitude, altitude, roll, pitch, yaw, u, v, heading] => {
                 if !longitude.is_empty() {
-                    coords.longitude = Some(f64::from_str(longitude)?);
+                    coords.longitude = Some(parse_numeric("longitude", longitude)?);
                 }
                 if !latitude.is_empty() {
-                    coords.latitude = Some(f64::from_str(latitude)?);
+                    coords.latitude = Some(parse_numeric("latitude", latitude)?);
                 }
                 if !altitude.is_empty() {
-                    coords.altitude = Some(f64::from_str(altitude)?);
+                    coords.altitude = Some(parse_numeric("altitude", altitude)?);
                 }
                 if !roll.is_empty() {
-                    coords.roll = Some(f64::from_str(roll)?);
+                    coords.roll = Some(parse_numeric("roll", roll)?);
                 }
                 if !pitch.is_empty() {
-                    coords.pitch = Some(f64::from_str(pitch)?);
+                    coords.pitch = Some(parse_numeric("pitch", pitch)?);
                 }
                 if !yaw.is_empty() {
-                    coords.yaw = Some(f64::from_str(yaw)?);
+                    coords.yaw = Some(parse_numeric("yaw", yaw)?);
                 }
                 if !u.is_empty() {
-                    coords.u = Some(f64::from_str(u)?);
+                    coords.u = Some(parse_numeric("u", u)?);
                 }
                 if !v.is_empty() {
-                    coords.v = Some(f64::from_str(v)?);
+                    coords.v = Some(parse_numeric("v", v)?);
                 }
                 if !heading.is_empty() {
-                    coords.heading = Some(f64::from_str(heading)?);
+                    coords.heading = Some(parse_numeric("heading", heading)?);
                 }
             }
             _ => return Err(ParseError::InvalidCoordinateFormat),
@@ -986,7 +1905,7 @@ impl FromStr for Coords {
 }
 
 impl Display for Coords {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let has_orientation = self.yaw.is_some() || self.pitch.is_some() || self.roll.is_some();
         let has_uv = self.u.is_some() || self.v.is_some();
         if self.heading.is_some() || (has_orientation && has_uv) {
@@ -1036,19 +1955,19 @@ impl Display for Coords {
     }
 }
 
-fn join<'a>(iter: impl Iterator<Item = &'a str>, sep: &'a str) -> String {
+fn join<V: Display>(iter: impl Iterator<Item = V>, sep: &str) -> String {
     iter.fold(String::new(), |mut acc, v| {
         if !acc.is_empty() {
             acc += sep;
         }
-        acc + v
+        acc + &v.to_string()
     })
 }
 
 struct NoneAsEmpty<V>(Option<V>);
 
 impl<V: Display> Display for NoneAsEmpty<V> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         if let Some(v) = &self.0 {
             v.fmt(f)
         } else {
@@ -1060,14 +1979,312 @@ impl<V: Display> Display for NoneAsEmpty<V> {
 fn to_index(i: u8) -> Cow<'static, str> {
     match i {
         0 => Cow::Borrowed(""),
-        1 => Cow::Borrowed("2"),
-        2 => Cow::Borrowed("3"),
-        3 => Cow::Borrowed("4"),
-        4 => Cow::Borrowed("5"),
-        5 => Cow::Borrowed("6"),
-        6 => Cow::Borrowed("7"),
-        7 => Cow::Borrowed("8"),
-        8 => Cow::Borrowed("9"),
-        i => Cow::Owned((i + 1).to_string()),
+        i => Cow::Owned((u16::from(i) + 1).to_string()),
+    }
+}
+
+/// Parses the zero-based index of an indexed property name, i.e. `{prefix}` is index 0 and
+/// `{prefix}{n}` is index `n - 1`. `{prefix}1` is an alias for `{prefix}`, as written by some
+/// recorders.
+fn indexed(name: &str, prefix: &str) -> Option<u8> {
+    let index = name.strip_prefix(prefix)?;
+    if index.is_empty() {
+        return Some(0);
+    }
+    if index.starts_with('0') || !index.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    match index.parse::<u16>().ok()? {
+        n @ 1..=256 => Some((n - 1) as u8),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+
+    #[test]
+    fn test_type_tag_order() {
+        let p = Property::from_str("Type=FixedWing+Air+Medium").unwrap();
+        assert_eq!(p.to_string(), "Type=Air+Medium+FixedWing");
+        assert_eq!(Property::from_str(&p.to_string()).unwrap(), p);
+
+        let p = Property::from_str("Type=Misc+Explosion+Tanker").unwrap();
+        assert_eq!(
+            p,
+            Property::Type(BTreeSet::from([
+                Tag::Misc,
+                Tag::Explosion,
+                Tag::Unknown("Tanker".to_string())
+            ]))
+        );
+        assert_eq!(p.to_string(), "Type=Misc+Explosion+Tanker");
+    }
+
+    #[test]
+    fn test_empty_type() {
+        let prop = Property::from_str("Type=").unwrap();
+        assert_eq!(prop, Property::Type(BTreeSet::new()));
+        assert_eq!(prop.to_string(), "Type=");
+    }
+
+    #[test]
+    fn test_to_kv() {
+        let kv = |p: &str| Property::from_str(p).unwrap().to_kv();
+        assert_eq!(kv("T=1|2|3"), ("T".to_string(), "1|2|3".to_string()));
+        assert_eq!(
+            kv("FuelWeight2=100"),
+            ("FuelWeight2".to_string(), "100".to_string())
+        );
+        assert_eq!(
+            kv("Label=a=b\\,c"),
+            ("Label".to_string(), "a=b\\,c".to_string())
+        );
+        assert_eq!(kv("Foo=Bar"), ("Foo".to_string(), "Bar".to_string()));
+    }
+
+    #[test]
+    fn test_fuel_indices() {
+        let prop = |p: &str| Property::from_str(p).unwrap();
+        assert_eq!(prop("FuelWeight=1"), Property::FuelWeight(0, 1.0));
+        assert_eq!(prop("FuelWeight1=1"), Property::FuelWeight(0, 1.0));
+        assert_eq!(prop("FuelWeight2=1"), Property::FuelWeight(1, 1.0));
+        assert_eq!(prop("FuelWeight10=1"), Property::FuelWeight(9, 1.0));
+        assert_eq!(prop("FuelVolume3=1"), Property::FuelVolume(2, 1.0));
+        assert_eq!(
+            prop("FuelFlowWeight12=1"),
+            Property::FuelFlowWeight(11, 1.0)
+        );
+        assert_eq!(
+            prop("FuelFlowVolume256=1"),
+            Property::FuelFlowVolume(255, 1.0)
+        );
+        assert_eq!(prop("FuelFlowWeight1=1"), Property::FuelFlowWeight(0, 1.0));
+        for name in [
+            "FuelWeight0",
+            "FuelWeight01",
+            "FuelWeight02",
+            "FuelWeight257",
+            "FuelWeightX",
+        ] {
+            assert_eq!(
+                prop(&format!("{name}=1")),
+                Property::Unknown(name.to_string(), "1".to_string())
+            );
+        }
+        for p in ["FuelWeight=1", "FuelVolume10=2", "FuelFlowVolume256=3"] {
+            assert_eq!(prop(p).to_string(), p);
+        }
+        assert_eq!(prop("FuelWeight1=1").to_string(), "FuelWeight=1");
+    }
+
+    #[test]
+    fn test_coords_constructors() {
+        assert_eq!(Coords::lla(1.0, 2.0, 3.0).to_string(), "1|2|3");
+        assert_eq!(
+            Coords::with_orientation(1.0, 2.0, 3.0, 4.0, 5.0, 6.0).to_string(),
+            "1|2|3|4|5|6"
+        );
+        assert_eq!(Coords::flat(4.0, 5.0, 3.0).to_string(), "||3|4|5");
+        assert_eq!(
+            Coords::from_str("||3|4|5").unwrap(),
+            Coords::flat(4.0, 5.0, 3.0)
+        );
+    }
+
+    #[test]
+    fn test_property_kind() {
+        let prop = Property::from_str("FuelWeight3=1").unwrap();
+        assert_eq!(PropertyKind::from(&prop), PropertyKind::FuelWeight);
+        assert_eq!(PropertyKind::from("FuelWeight3"), PropertyKind::FuelWeight);
+        assert_eq!(PropertyKind::from("HDG"), PropertyKind::HDG);
+        assert_eq!(PropertyKind::from("Foo"), PropertyKind::Unknown);
+        assert_eq!(PropertyKind::from("FuelWeight1"), PropertyKind::FuelWeight);
+        assert_eq!(PropertyKind::from("FuelWeight0"), PropertyKind::Unknown);
+        assert_eq!(PropertyKind::LandingGear.to_string(), "LandingGear");
+
+        let mut props = ["T=1|2|3", "Name=F-16C", "HDG=90"]
+            .map(|p| Property::from_str(p).unwrap())
+            .to_vec();
+        props.retain(|p| p.kind() != PropertyKind::Name);
+        assert_eq!(props.len(), 2);
+    }
+
+    #[test]
+    fn test_mode() {
+        let prop = |p: &str| Property::from_str(p).unwrap();
+        assert_eq!(prop("RadarMode=0").mode(), Some(Mode::Off));
+        assert_eq!(prop("EngagementMode2=2").mode(), Some(Mode::On(2.0)));
+        assert_eq!(prop("IAS=0").mode(), None);
+        assert!(prop("RadarMode=1").is_radar_on());
+        assert!(!prop("RadarMode=0.0").is_radar_on());
+        assert!(!prop("EngagementMode=1").is_radar_on());
+    }
+
+    #[test]
+    fn test_transponder_codes() {
+        assert!(Property::Squawk("7700".to_string()).squawk_is_valid_octal());
+        assert!(Property::Squawk("0017".to_string()).squawk_is_valid_octal());
+        assert!(!Property::Squawk("7800".to_string()).squawk_is_valid_octal());
+        assert!(!Property::Squawk("12345".to_string()).squawk_is_valid_octal());
+        assert!(!Property::Squawk("A1".to_string()).squawk_is_valid_octal());
+        assert!(!Property::ICAO24("7700".to_string()).squawk_is_valid_octal());
+
+        assert!(Property::ICAO24("3C6DD2".to_string()).icao24_is_valid());
+        assert!(!Property::ICAO24("3C6DZ2".to_string()).icao24_is_valid());
+        assert!(!Property::ICAO24("3C6DD".to_string()).icao24_is_valid());
+    }
+
+    #[test]
+    fn test_tag_predicates() {
+        let p = Property::from_str("Type=Weapon+Missile").unwrap();
+        assert!(p.is_weapon());
+        assert!(!p.is_air());
+        assert!(p.has_tag(&Tag::Missile));
+
+        let p = Property::from_str("Type=Air+Rotorcraft").unwrap();
+        assert!(p.is_air());
+        assert!(!p.is_ground() && !p.is_sea() && !p.is_weapon());
+
+        assert!(!Property::Name("Air".to_string()).is_air());
+    }
+
+    #[test]
+    fn test_distance_to() {
+        let paris = Coords::default().position(48.8566, 2.3522, 0.0);
+        let london = Coords::default().position(51.5074, -0.1278, 0.0);
+        assert!((paris.distance_to(&london).unwrap() - 343_556.0).abs() < 100.0);
+
+        let new_york = Coords::default().position(40.7128, -74.0060, 0.0);
+        let los_angeles = Coords::default().position(34.0522, -118.2437, 0.0);
+        assert!((new_york.distance_to(&los_angeles).unwrap() - 3_935_751.0).abs() < 100.0);
+
+        let a = Coords::default().position(0.0, 0.0, 0.0);
+        let b = Coords::default().position(0.0, 0.0, 1000.0);
+        assert_eq!(a.distance_to(&b), Some(1000.0));
+
+        assert_eq!(a.distance_to(&Coords::default().uv(1.0, 2.0)), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        let coords = Coords::default().position(1.0, 2.0, 3.0);
+        assert_eq!(coords.validate(40.0, 170.0), Ok(()));
+        assert_eq!(coords.validate(90.0, 0.0), Err(CoordError::Latitude(91.0)));
+        assert_eq!(
+            coords.validate(0.0, -183.0),
+            Err(CoordError::Longitude(-181.0))
+        );
+        assert!(matches!(
+            Coords::default()
+                .position(0.0, 0.0, f64::NAN)
+                .validate(0.0, 0.0),
+            Err(CoordError::Altitude(_))
+        ));
+    }
+
+    #[test]
+    fn test_lerp() {
+        let a = Coords::default()
+            .position(10.0, 20.0, 1000.0)
+            .orientation(350.0, 0.0, 170.0);
+        let b = Coords::default()
+            .position(20.0, 30.0, 2000.0)
+            .orientation(30.0, 10.0, -170.0);
+        let c = a.lerp(&b, 0.5);
+        assert_eq!(
+            c,
+            Coords::default()
+                .position(15.0, 25.0, 1500.0)
+                .orientation(10.0, 5.0, 180.0)
+        );
+        assert_eq!(
+            a.lerp(&Coords::default().uv(1.0, 2.0), 0.5),
+            Coords::default()
+        );
+    }
+
+    #[test]
+    fn test_named_color_roundtrip() {
+        use Color::*;
+        for color in [Red, Orange, Yellow, Green, Cyan, Blue, Violet, Grey, White] {
+            let p = Property::Color(color);
+            assert_eq!(Property::from_str(&p.to_string()).unwrap(), p);
+        }
+    }
+
+    #[test]
+    fn test_rgb_color() {
+        assert_eq!(Color::from("#1a2B3c"), Color::Rgb(0x1a, 0x2b, 0x3c));
+        assert_eq!(Color::from("#f80"), Color::Rgb(0xff, 0x88, 0x00));
+        assert_eq!(Color::Rgb(0x1a, 0x2b, 0x3c).to_string(), "#1A2B3C");
+        assert_eq!(Color::from("#12345"), Color::Unknown("#12345".to_string()));
+        assert_eq!(Color::from("Pink"), Color::Unknown("Pink".to_string()));
+    }
+
+    #[test]
+    fn test_color_to_rgb() {
+        assert_eq!(Color::Orange.to_rgb(), Some([255, 128, 0]));
+        assert_eq!(Color::Rgb(1, 2, 3).to_rgb(), Some([1, 2, 3]));
+        assert_eq!(Color::Unknown("Pink".to_string()).to_rgb(), None);
+        assert_eq!(Color::from([0, 255, 255]), Color::Cyan);
+        assert_eq!(Color::from([0, 254, 255]), Color::Rgb(0, 254, 255));
+
+        use Color::*;
+        for color in [Red, Orange, Yellow, Green, Cyan, Blue, Violet, Grey, White] {
+            assert_eq!(Color::from(color.to_rgb().unwrap()), color);
+        }
+    }
+
+    #[test]
+    fn test_coalition() {
+        assert_eq!(
+            Property::from_str("Coalition=Enemies").unwrap(),
+            Property::Coalition(Coalition::Enemies)
+        );
+        let p = Property::from_str("Coalition=Red\\, Blue").unwrap();
+        assert_eq!(
+            p,
+            Property::Coalition(Coalition::Unknown("Red, Blue".to_string()))
+        );
+        assert_eq!(p.to_string(), "Coalition=Red\\, Blue");
+    }
+
+    #[test]
+    fn test_label_escaping() {
+        let p = Property::Label("foo,bar\\baz".to_string());
+        assert_eq!(p.to_string(), "Label=foo\\,bar\\\\baz");
+        assert_eq!(Property::from_str(&p.to_string()).unwrap(), p);
+    }
+
+    #[test]
+    fn test_unknown_color_and_tag_escaping() {
+        let p = Property::Color(Color::Unknown("a,b".to_string()));
+        assert_eq!(p.to_string(), "Color=a\\,b");
+        assert_eq!(Property::from_str(&p.to_string()).unwrap(), p);
+
+        let p = Property::Type(
+            [Tag::Air, Tag::Unknown("X,Y".to_string())]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(p.to_string(), "Type=Air+X\\,Y");
+        assert_eq!(Property::from_str(&p.to_string()).unwrap(), p);
+    }
+
+    #[test]
+    fn test_unit() {
+        assert_eq!(Property::AGL(1.0).unit(), Some("m"));
+        assert_eq!(Property::TAS(1.0).unit(), Some("m/s"));
+        assert_eq!(Property::HDG(1.0).unit(), Some("deg"));
+        assert_eq!(Property::Throttle(1.0).unit(), Some("ratio"));
+        assert_eq!(Property::FuelFlowWeight(0, 1.0).unit(), Some("kg/hour"));
+        assert_eq!(Property::VerticalGForce(1.0).unit(), Some("g"));
+        assert_eq!(Property::Name("F-16C".to_string()).unit(), None);
+        assert_eq!(Property::CallSign("Viper".to_string()).unit(), None);
+        assert_eq!(PropertyKind::T.unit(), None);
     }
 }
@@ -1,51 +1,171 @@
-use std::fmt::Display;
-use std::str::FromStr;
+use alloc::vec::Vec;
+use core::fmt::Display;
+use core::str::FromStr;
 
-use super::Property;
+use super::{parse_id, Coords, Property, PropertyKind, UpdateRef};
 use crate::ParseError;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct Update {
     pub id: u64,
     pub props: Vec<Property>,
 }
 
-impl FromStr for Update {
-    type Err = ParseError;
-
-    fn from_str(line: &str) -> Result<Self, Self::Err> {
-        let (id, mut rest) = line.split_once(',').ok_or(ParseError::Eol)?;
-        let id = u64::from_str_radix(id, 16)?;
-        let mut props = Vec::new();
-
-        let mut prev = None;
-        let mut offset = 0;
-        for (i, ch) in rest.char_indices() {
-            if ch == ',' && prev != Some('\\') {
-                let (kv, r) = rest.split_at(i - offset);
-                rest = r.strip_prefix(',').unwrap_or(rest);
-                offset = i + 1;
-
-                props.push(Property::from_str(kv)?);
-            }
+impl Update {
+    /// The first property of the given kind. Updates usually contain each property at most once,
+    /// but this is not enforced.
+    pub fn get(&self, kind: PropertyKind) -> Option<&Property> {
+        self.props.iter().find(|p| p.kind() == kind)
+    }
 
-            prev = Some(ch);
+    /// The coordinates (`T`) of the update, see [`Update::get`].
+    pub fn coords(&self) -> Option<&Coords> {
+        match self.get(PropertyKind::T)? {
+            Property::T(coords) => Some(coords),
+            _ => None,
         }
+    }
 
-        if !rest.is_empty() {
-            props.push(Property::from_str(rest)?);
+    /// The name of the object, see [`Update::get`].
+    pub fn name(&self) -> Option<&str> {
+        match self.get(PropertyKind::Name)? {
+            Property::Name(name) => Some(name),
+            _ => None,
         }
+    }
 
-        Ok(Update { id, props })
+    /// The heading (`HDG`) of the object, see [`Update::get`].
+    /// Unit: deg
+    pub fn heading(&self) -> Option<f64> {
+        match self.get(PropertyKind::HDG)? {
+            Property::HDG(v) => Some(*v),
+            _ => None,
+        }
     }
-}
 
-impl Display for Update {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.id)?;
+    /// Formats the update like its `Display` implementation, but formats each of its properties
+    /// with `fmt_prop`, e.g. to write some of them differently.
+    pub(crate) fn fmt_with(
+        &self,
+        f: &mut core::fmt::Formatter<'_>,
+        mut fmt_prop: impl FnMut(&Property, &mut core::fmt::Formatter<'_>) -> core::fmt::Result,
+    ) -> core::fmt::Result {
+        write!(f, "{:x}", self.id)?;
         for p in &self.props {
-            write!(f, ",{p}")?;
+            f.write_str(",")?;
+            fmt_prop(p, f)?;
         }
         Ok(())
     }
 }
+
+impl Extend<Property> for Update {
+    fn extend<T: IntoIterator<Item = Property>>(&mut self, iter: T) {
+        self.props.extend(iter);
+    }
+}
+
+impl FromIterator<Property> for Update {
+    /// Collects the properties into an update with id `0`, which is reserved for global
+    /// properties. Set [`Update::id`] before writing the update.
+    fn from_iter<T: IntoIterator<Item = Property>>(iter: T) -> Self {
+        Update {
+            id: 0,
+            props: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl FromStr for Update {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let (id, rest) = line.split_once(',').ok_or(ParseError::Eol)?;
+        let id = parse_id("object id", id)?;
+        UpdateRef::new(id, rest).to_owned()
+    }
+}
+
+impl Display for Update {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.fmt_with(f, Property::fmt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn test_equals_sign_in_value() {
+        let update = Update::from_str("1,Label=x=5,Debug=a=b=c,Name=F-16C").unwrap();
+        assert_eq!(
+            update.props,
+            vec![
+                Property::Label("x=5".to_string()),
+                Property::Debug("a=b=c".to_string()),
+                Property::Name("F-16C".to_string()),
+            ]
+        );
+        assert_eq!(update.to_string(), "1,Label=x=5,Debug=a=b=c,Name=F-16C");
+        assert_eq!(
+            Property::from_str("Label=x=5").unwrap(),
+            Property::Label("x=5".to_string())
+        );
+    }
+
+    #[test]
+    fn test_hex_id_roundtrip() {
+        let update = Update {
+            id: 255,
+            props: vec![Property::Name("F-16C".to_string())],
+        };
+        assert_eq!(update.to_string(), "ff,Name=F-16C");
+        assert_eq!(Update::from_str(&update.to_string()).unwrap(), update);
+
+        let remove = crate::record::Record::Remove(255);
+        assert_eq!(remove.to_string(), "-ff");
+        assert_eq!(
+            crate::record::Record::from_str(&remove.to_string()).unwrap(),
+            remove
+        );
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut update = [Property::Name("F-16C".to_string())]
+            .into_iter()
+            .collect::<Update>();
+        update.id = 0x3f02;
+        update.extend(
+            [Property::HDG(90.0), Property::AGL(10.0)]
+                .into_iter()
+                .take(1),
+        );
+        assert_eq!(
+            update,
+            Update {
+                id: 0x3f02,
+                props: vec![Property::Name("F-16C".to_string()), Property::HDG(90.0)],
+            }
+        );
+    }
+
+    #[test]
+    fn test_get() {
+        let update = Update::from_str("1,T=1|2|3,Name=F-16C,HDG=90,HDG=95").unwrap();
+        assert_eq!(
+            update.get(PropertyKind::Name),
+            Some(&Property::Name("F-16C".to_string()))
+        );
+        assert_eq!(update.get(PropertyKind::IAS), None);
+        assert_eq!(update.coords(), Some(&Coords::lla(1.0, 2.0, 3.0)));
+        assert_eq!(update.name(), Some("F-16C"));
+        assert_eq!(update.heading(), Some(90.0));
+    }
+}
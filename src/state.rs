@@ -0,0 +1,509 @@
+use std::collections::{HashMap, HashSet};
+use std::iter::Peekable;
+
+use crate::record::{Coords, GlobalProperty, Property, Record, Update};
+use crate::ParseError;
+
+/// Keeps track of the full state of all objects by merging the incremental updates of a
+/// recording.
+///
+/// Records must be fed in order and with relative coordinates, i.e. from a [`crate::Parser`]
+/// without [`crate::Parser::with_reference_resolution`], as the tracker applies the reference
+/// offsets itself.
+#[derive(Debug, Default, Clone)]
+pub struct StateTracker {
+    objects: HashMap<u64, ObjectState>,
+    retain_removed: bool,
+    removed: HashMap<u64, RemovedObject>,
+    time: f64,
+    reference_longitude: f64,
+    reference_latitude: f64,
+}
+
+/// The accumulated state of a single object.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ObjectState {
+    pub id: u64,
+
+    /// Absolute coordinates of the object (reference offsets already applied).
+    pub coords: Coords,
+
+    /// All properties except for `T`, at most one per property name.
+    pub props: Vec<Property>,
+}
+
+/// The last known state of an object that has been removed, see
+/// [`StateTracker::retain_removed`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemovedObject {
+    /// The time of the frame the object has been removed in.
+    /// Unit: s
+    pub time: f64,
+
+    /// The final state of the object.
+    pub state: ObjectState,
+}
+
+impl StateTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the final state of removed objects (e.g. to render fade-outs) instead of discarding
+    /// them, see [`StateTracker::removed`]. Disabled by default. A removed object is forgotten
+    /// once its id receives another update.
+    pub fn retain_removed(mut self, retain: bool) -> Self {
+        self.retain_removed = retain;
+        self
+    }
+
+    pub fn apply(&mut self, record: &Record) {
+        match record {
+            Record::GlobalProperty(GlobalProperty::ReferenceLongitude(v)) => {
+                self.reference_longitude = *v;
+            }
+            Record::GlobalProperty(GlobalProperty::ReferenceLatitude(v)) => {
+                self.reference_latitude = *v;
+            }
+            Record::GlobalProperty(_) | Record::Event(_) => {}
+            Record::Remove(id) => {
+                if let Some(state) = self.objects.remove(id) {
+                    if self.retain_removed {
+                        let time = self.time;
+                        self.removed.insert(*id, RemovedObject { time, state });
+                    }
+                }
+            }
+            Record::Frame(time) => self.time = *time,
+            Record::Update(update) => self.apply_update(update),
+        }
+    }
+
+    fn apply_update(&mut self, update: &Update) {
+        if self.retain_removed {
+            self.removed.remove(&update.id);
+        }
+
+        let state = self
+            .objects
+            .entry(update.id)
+            .or_insert_with(|| ObjectState {
+                id: update.id,
+                ..Default::default()
+            });
+
+        for prop in &update.props {
+            if let Property::T(coords) = prop {
+                state
+                    .coords
+                    .update(coords, self.reference_latitude, self.reference_longitude);
+            } else if let Some(existing) = state.props.iter_mut().find(|p| p.same_key(prop)) {
+                *existing = prop.clone();
+            } else {
+                state.props.push(prop.clone());
+            }
+        }
+    }
+
+    /// Returns the properties of `update` that would change the tracked state of object `id`, i.e.
+    /// the sparse update Tacview would write. Coordinates are compared component-wise (taking the
+    /// reference offsets into account) and only the changed components are kept. The update is
+    /// returned as is for untracked objects. Does not apply the update.
+    pub fn diff(&self, id: u64, update: &Update) -> Update {
+        let Some(state) = self.objects.get(&id) else {
+            return update.clone();
+        };
+
+        let props = update
+            .props
+            .iter()
+            .filter_map(|prop| match prop {
+                Property::T(coords) => {
+                    let current = &state.coords;
+                    let changed = |new: Option<f64>, offset: f64, current: Option<f64>| {
+                        new.filter(|v| Some(v + offset) != current)
+                    };
+                    let coords = Coords {
+                        longitude: changed(
+                            coords.longitude,
+                            self.reference_longitude,
+                            current.longitude,
+                        ),
+                        latitude: changed(
+                            coords.latitude,
+                            self.reference_latitude,
+                            current.latitude,
+                        ),
+                        altitude: changed(coords.altitude, 0.0, current.altitude),
+                        u: changed(coords.u, 0.0, current.u),
+                        v: changed(coords.v, 0.0, current.v),
+                        roll: changed(coords.roll, 0.0, current.roll),
+                        pitch: changed(coords.pitch, 0.0, current.pitch),
+                        yaw: changed(coords.yaw, 0.0, current.yaw),
+                        heading: changed(coords.heading, 0.0, current.heading),
+                    };
+                    (coords != Coords::default()).then_some(Property::T(coords))
+                }
+                prop => (!state.props.contains(prop)).then(|| prop.clone()),
+            })
+            .collect();
+
+        Update { id, props }
+    }
+
+    /// Applies records until the first frame at or after `time` (that has not been applied yet)
+    /// has been applied completely, i.e. including all its updates, and returns a snapshot of all
+    /// objects sorted by id. Stops before the following frame, so `records` can be advanced
+    /// further later on. Also stops at the end of the input, and at the first error, which is
+    /// returned.
+    ///
+    /// `records` is usually a peekable [`crate::Parser`]:
+    /// `tracker.advance_to(&mut parser.by_ref().peekable(), 300.0)`.
+    pub fn advance_to<I>(
+        &mut self,
+        records: &mut Peekable<I>,
+        time: f64,
+    ) -> Result<Vec<(u64, ObjectState)>, ParseError>
+    where
+        I: Iterator<Item = Result<Record, ParseError>>,
+    {
+        let mut reached = false;
+        while let Some(record) =
+            records.next_if(|record| !(reached && matches!(record, Ok(Record::Frame(_)))))
+        {
+            let record = record?;
+            if let Record::Frame(t) = record {
+                reached = t >= time;
+            }
+            self.apply(&record);
+        }
+
+        let mut snapshot = self
+            .objects
+            .iter()
+            .map(|(id, state)| (*id, state.clone()))
+            .collect::<Vec<_>>();
+        snapshot.sort_by_key(|(id, _)| *id);
+        Ok(snapshot)
+    }
+
+    pub fn get(&self, id: u64) -> Option<&ObjectState> {
+        self.objects.get(&id)
+    }
+
+    /// All objects that are currently alive, in no particular order.
+    pub fn objects(&self) -> impl Iterator<Item = &ObjectState> {
+        self.objects.values()
+    }
+
+    /// All removed objects retained with [`StateTracker::retain_removed`], in no particular order.
+    pub fn removed(&self) -> impl Iterator<Item = &RemovedObject> {
+        self.removed.values()
+    }
+
+    /// The removed objects that have been removed at or after `time`, e.g. to render the objects
+    /// removed within the last few seconds.
+    /// Unit: s
+    pub fn removed_since(&self, time: f64) -> impl Iterator<Item = &RemovedObject> {
+        self.removed.values().filter(move |r| r.time >= time)
+    }
+
+    /// Forgets all removed objects retained so far.
+    pub fn clear_removed(&mut self) {
+        self.removed.clear();
+    }
+
+    /// All objects whose `Parent` is `id`, in no particular order.
+    pub fn children_of(&self, id: u64) -> impl Iterator<Item = &ObjectState> {
+        self.objects
+            .values()
+            .filter(move |state| state.parent() == Some(id))
+    }
+
+    /// The `Parent` of object `id`, if both are alive.
+    pub fn parent_of(&self, id: u64) -> Option<&ObjectState> {
+        self.get(self.get(id)?.parent()?)
+    }
+
+    /// The objects linked via `Next`, starting with `start_id` (e.g. a route of waypoints). Stops
+    /// at the first object that is not alive, or when the chain loops back to an object already
+    /// part of it.
+    pub fn waypoint_chain(&self, start_id: u64) -> Vec<&ObjectState> {
+        let mut chain = Vec::new();
+        let mut seen = HashSet::new();
+        let mut next = Some(start_id);
+        while let Some(state) = next
+            .filter(|id| seen.insert(*id))
+            .and_then(|id| self.get(id))
+        {
+            chain.push(state);
+            next = state.find(|p| match p {
+                Property::Next(id) => Some(*id),
+                _ => None,
+            });
+        }
+        chain
+    }
+
+    /// The time of the current frame.
+    /// Unit: s
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+}
+
+impl ObjectState {
+    /// Returns the first value `f` returns for any of the object's properties.
+    pub fn find<'a, T>(&'a self, f: impl FnMut(&'a Property) -> Option<T>) -> Option<T> {
+        self.props.iter().find_map(f)
+    }
+
+    /// The altitude above mean sea level, i.e. the altitude of the object's coordinates.
+    /// Unit: m
+    pub fn altitude_msl(&self) -> Option<f64> {
+        self.coords.altitude
+    }
+
+    /// The altitude above ground level, i.e. the object's `AGL` property.
+    /// Unit: m
+    pub fn altitude_agl(&self) -> Option<f64> {
+        self.find(|p| match p {
+            Property::AGL(v) => Some(*v),
+            _ => None,
+        })
+    }
+
+    /// The elevation of the terrain below the object above mean sea level, as implied by the
+    /// difference of its [`ObjectState::altitude_msl`] and [`ObjectState::altitude_agl`].
+    /// Unit: m
+    pub fn terrain_elevation(&self) -> Option<f64> {
+        Some(self.altitude_msl()? - self.altitude_agl()?)
+    }
+
+    fn parent(&self) -> Option<u64> {
+        self.find(|p| match p {
+            Property::Parent(id) => Some(*id),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_line;
+
+    #[test]
+    fn test_state_tracker() {
+        let mut tracker = StateTracker::new();
+        for line in [
+            "0,ReferenceLongitude=10",
+            "0,ReferenceLatitude=20",
+            "1,T=1|2|3,Name=F-16C,FuelWeight=100,FuelWeight2=200",
+            "2,T=4|5|6,Name=MiG-29",
+            "1,T=|3|,HDG=90,FuelWeight2=150",
+        ] {
+            let record = parse_line(line).unwrap().unwrap();
+            tracker.apply(&record);
+        }
+        tracker.apply(&Record::Frame(1.0));
+        tracker.apply(&Record::Remove(2));
+
+        assert_eq!(tracker.time(), 1.0);
+        assert!(tracker.get(2).is_none());
+        assert_eq!(tracker.objects().count(), 1);
+        assert_eq!(
+            tracker.get(1),
+            Some(&ObjectState {
+                id: 1,
+                coords: Coords::default().position(23.0, 11.0, 3.0),
+                props: vec![
+                    Property::Name("F-16C".to_string()),
+                    Property::FuelWeight(0, 100.0),
+                    Property::FuelWeight(1, 150.0),
+                    Property::HDG(90.0),
+                ],
+            })
+        );
+        assert_eq!(
+            tracker.get(1).unwrap().find(|p| match p {
+                Property::Name(name) => Some(name.as_str()),
+                _ => None,
+            }),
+            Some("F-16C")
+        );
+    }
+
+    #[test]
+    fn test_reference_change() {
+        let mut tracker = StateTracker::new();
+        for line in [
+            "0,ReferenceLongitude=10",
+            "0,ReferenceLatitude=20",
+            "1,T=1|2|3",
+            "0,ReferenceLongitude=30",
+            "0,ReferenceLatitude=40",
+            "2,T=1|2|3",
+            "1,T=|2|",
+        ] {
+            tracker.apply(&parse_line(line).unwrap().unwrap());
+        }
+        assert_eq!(
+            tracker.get(1).unwrap().coords,
+            Coords::default().position(42.0, 11.0, 3.0)
+        );
+        assert_eq!(
+            tracker.get(2).unwrap().coords,
+            Coords::default().position(42.0, 31.0, 3.0)
+        );
+    }
+
+    #[test]
+    fn test_retain_removed() {
+        let mut tracker = StateTracker::new().retain_removed(true);
+        for line in ["1,T=1|2|3,Name=F-16C", "2,T=4|5|6,Name=MiG-29"] {
+            tracker.apply(&parse_line(line).unwrap().unwrap());
+        }
+        tracker.apply(&Record::Frame(1.0));
+        tracker.apply(&Record::Remove(1));
+        tracker.apply(&Record::Frame(2.0));
+        tracker.apply(&Record::Remove(2));
+        tracker.apply(&Record::Remove(3));
+
+        assert_eq!(tracker.objects().count(), 0);
+        assert_eq!(tracker.removed().count(), 2);
+        let recent = tracker.removed_since(1.5).collect::<Vec<_>>();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].time, 2.0);
+        assert_eq!(recent[0].state.id, 2);
+        assert_eq!(
+            recent[0].state.coords,
+            Coords::default().position(5.0, 4.0, 6.0)
+        );
+
+        tracker.apply(&parse_line("2,T=4|5|6").unwrap().unwrap());
+        assert_eq!(tracker.removed().count(), 1);
+        tracker.clear_removed();
+        assert_eq!(tracker.removed().count(), 0);
+
+        let mut tracker = StateTracker::new();
+        tracker.apply(&parse_line("1,T=1|2|3").unwrap().unwrap());
+        tracker.apply(&Record::Remove(1));
+        assert_eq!(tracker.removed().count(), 0);
+    }
+
+    #[test]
+    fn test_advance_to() {
+        let acmi = "FileType=text/acmi/tacview\nFileVersion=2.2\n\
+                    #0\n1,T=1|2|3\n#1\n2,T=4|5|6\n#2.5\n1,T=7|8|9\n-2\n#3\n1,T=0|0|0\n";
+        let mut records = crate::Parser::new(acmi.as_bytes()).unwrap().peekable();
+        let mut tracker = StateTracker::new();
+
+        let snapshot = tracker.advance_to(&mut records, 2.0).unwrap();
+        assert_eq!(tracker.time(), 2.5);
+        assert_eq!(
+            snapshot
+                .iter()
+                .map(|(id, state)| (*id, state.coords.clone()))
+                .collect::<Vec<_>>(),
+            vec![(1, Coords::default().position(8.0, 7.0, 9.0))]
+        );
+        assert_eq!(
+            records.peek().unwrap().as_ref().unwrap(),
+            &Record::Frame(3.0)
+        );
+
+        let snapshot = tracker.advance_to(&mut records, 10.0).unwrap();
+        assert_eq!(tracker.time(), 3.0);
+        assert_eq!(
+            snapshot[0].1.coords,
+            Coords::default().position(0.0, 0.0, 0.0)
+        );
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn test_relations() {
+        let mut tracker = StateTracker::new();
+        for line in [
+            "1,Name=F-16C",
+            "2,Name=AIM-9,Parent=1",
+            "3,Name=AIM-120,Parent=1",
+            "a,Type=Navaid+Static+Waypoint,Next=b",
+            "b,Type=Navaid+Static+Waypoint,Next=c",
+            "c,Type=Navaid+Static+Waypoint,Next=a",
+        ] {
+            tracker.apply(&parse_line(line).unwrap().unwrap());
+        }
+
+        let mut children = tracker.children_of(1).map(|s| s.id).collect::<Vec<_>>();
+        children.sort();
+        assert_eq!(children, vec![2, 3]);
+        assert_eq!(tracker.parent_of(2).map(|s| s.id), Some(1));
+        assert!(tracker.parent_of(1).is_none());
+
+        let chain = |start| {
+            tracker
+                .waypoint_chain(start)
+                .iter()
+                .map(|s| s.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(chain(0xb), vec![0xb, 0xc, 0xa]);
+        assert_eq!(chain(0x1), vec![0x1]);
+        assert!(chain(0x42).is_empty());
+    }
+
+    #[test]
+    fn test_altitudes() {
+        let mut tracker = StateTracker::new();
+        tracker.apply(&parse_line("1,T=1|2|1500").unwrap().unwrap());
+        let state = tracker.get(1).unwrap();
+        assert_eq!(state.altitude_msl(), Some(1500.0));
+        assert_eq!(state.altitude_agl(), None);
+        assert_eq!(state.terrain_elevation(), None);
+
+        tracker.apply(&parse_line("1,AGL=1200").unwrap().unwrap());
+        let state = tracker.get(1).unwrap();
+        assert_eq!(state.altitude_agl(), Some(1200.0));
+        assert_eq!(state.terrain_elevation(), Some(300.0));
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut tracker = StateTracker::new();
+        for line in [
+            "0,ReferenceLongitude=10",
+            "1,T=1|2|3|0|5|90,Name=F-16C,HDG=90",
+        ] {
+            tracker.apply(&parse_line(line).unwrap().unwrap());
+        }
+
+        let Some(Record::Update(update)) =
+            parse_line("1,T=1|2.5|3|0|4|90,Name=F-16C,HDG=95,IAS=100").unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(
+            tracker.diff(1, &update),
+            Update {
+                id: 1,
+                props: vec![
+                    Property::T(Coords {
+                        latitude: Some(2.5),
+                        pitch: Some(4.0),
+                        ..Default::default()
+                    }),
+                    Property::HDG(95.0),
+                    Property::IAS(100.0),
+                ]
+            }
+        );
+        assert_eq!(tracker.diff(2, &update), update);
+
+        let Some(Record::Update(update)) = parse_line("1,T=1|2|3,Name=F-16C").unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(tracker.diff(1, &update).props, vec![]);
+    }
+}
@@ -0,0 +1,40 @@
+//! Conversions between the SI units used by ACMI files and the units commonly used in aviation.
+
+const METERS_PER_FOOT: f64 = 0.3048;
+const KNOTS_PER_MPS: f64 = 3600.0 / 1852.0;
+
+pub fn meters_to_feet(m: f64) -> f64 {
+    m / METERS_PER_FOOT
+}
+
+pub fn feet_to_meters(ft: f64) -> f64 {
+    ft * METERS_PER_FOOT
+}
+
+pub fn mps_to_knots(mps: f64) -> f64 {
+    mps * KNOTS_PER_MPS
+}
+
+pub fn knots_to_mps(kt: f64) -> f64 {
+    kt / KNOTS_PER_MPS
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+    use crate::record::Property;
+
+    #[test]
+    fn test_conversions() {
+        let Property::IAS(ias) = Property::IAS(100.0) else {
+            unreachable!()
+        };
+        assert_eq!(format!("{:.1}", mps_to_knots(ias)), "194.4");
+        assert!((knots_to_mps(mps_to_knots(ias)) - ias).abs() < 1e-9);
+
+        assert!((meters_to_feet(3048.0) - 10_000.0).abs() < 1e-9);
+        assert!((feet_to_meters(10_000.0) - 3048.0).abs() < 1e-9);
+    }
+}
@@ -1,35 +1,253 @@
+use std::fmt::Display;
 use std::io::{self, Seek, Write};
+use std::mem;
 
-use zip::write::SimpleFileOptions;
+pub use zip::write::SimpleFileOptions;
+pub use zip::CompressionMethod;
 use zip::ZipWriter;
 
-use crate::record::Record;
+use crate::parser::lines;
+use crate::record::{
+    Coalition, Coords, Country, GlobalProperty, Precision, Property, Record, Update,
+    FRAME_PRECISION,
+};
+
+const DEFAULT_ENTRY_NAME: &str = "track.txt.acmi";
 
 pub struct Writer<W> {
     wr: W,
+    precision: Option<u32>,
+    frame_precision: u32,
+    /// `(longitude, latitude)`
+    reference: Option<(f64, f64)>,
+    reference_written: bool,
+    compact_events: bool,
+    coords_precision: Option<CoordsPrecision>,
+    normalize_identifiers: bool,
+}
+
+/// The number of decimal places of each coordinate component, see
+/// [`Writer::with_coords_precision`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoordsPrecision {
+    /// Longitude and latitude (deg).
+    pub lonlat: u32,
+    /// Altitude and the native `u` and `v` coordinates of flat worlds (m).
+    pub alt: u32,
+    /// Roll, pitch, yaw and heading (deg).
+    pub angles: u32,
 }
 
 impl<W> Writer<W>
 where
     W: Write,
 {
-    pub fn new(mut wr: W) -> Result<Self, io::Error> {
+    pub fn new(wr: W) -> Result<Self, io::Error> {
+        Self::with_version(wr, 2)
+    }
+
+    /// Like [`Writer::new`], but writes `FileVersion=2.{minor}` instead of `FileVersion=2.2`, e.g.
+    /// to preserve the version of a transcoded file. Only versions `2.0` to `2.2` are supported.
+    pub fn with_version(mut wr: W, minor: u8) -> Result<Self, io::Error> {
+        if minor > 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported file version `2.{minor}`"),
+            ));
+        }
+
         writeln!(wr, "FileType=text/acmi/tacview")?;
-        writeln!(wr, "FileVersion=2.2")?;
-        Ok(Self { wr })
+        writeln!(wr, "FileVersion=2.{minor}")?;
+        Ok(Self {
+            wr,
+            precision: None,
+            frame_precision: FRAME_PRECISION,
+            reference: None,
+            reference_written: false,
+            compact_events: false,
+            coords_precision: None,
+            normalize_identifiers: false,
+        })
     }
 
     pub fn new_compressed(wr: W) -> Result<Writer<impl Write>, io::Error>
+    where
+        W: Seek,
+    {
+        Self::new_compressed_named(wr, DEFAULT_ENTRY_NAME)
+    }
+
+    /// Like [`Writer::new_compressed`], but with custom zip options, e.g. to choose the
+    /// compression method and level.
+    pub fn new_compressed_with_options(
+        wr: W,
+        options: SimpleFileOptions,
+    ) -> Result<Writer<impl Write>, io::Error>
+    where
+        W: Seek,
+    {
+        Self::compressed(wr, DEFAULT_ENTRY_NAME, options)
+    }
+
+    /// Like [`Writer::new_compressed`], but with a custom name for the recording inside the zip
+    /// archive. The name must not be empty nor contain path separators.
+    pub fn new_compressed_named(wr: W, entry_name: &str) -> Result<Writer<impl Write>, io::Error>
+    where
+        W: Seek,
+    {
+        if entry_name.is_empty() || entry_name.contains(['/', '\\']) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid zip entry name `{entry_name}`"),
+            ));
+        }
+
+        Self::compressed(wr, entry_name, SimpleFileOptions::default())
+    }
+
+    fn compressed(
+        wr: W,
+        entry_name: &str,
+        options: SimpleFileOptions,
+    ) -> Result<Writer<impl Write>, io::Error>
     where
         W: Seek,
     {
         let mut zip = ZipWriter::new(wr);
-        zip.start_file("track.txt.acmi", SimpleFileOptions::default())?;
+        zip.start_file(entry_name, options)?;
         Writer::new(zip)
     }
 
+    /// Round all numeric object properties and coordinates to at most `digits` decimal places
+    /// when writing them. Object ids, frame times and global properties are not affected.
+    pub fn with_precision(mut self, digits: u32) -> Self {
+        self.precision = Some(digits);
+        self
+    }
+
+    /// Round the components of object coordinates to at most the given decimal places when
+    /// writing them, instead of using the precision of [`Writer::with_precision`] (if any) for
+    /// them.
+    pub fn with_coords_precision(mut self, precision: CoordsPrecision) -> Self {
+        self.coords_precision = Some(precision);
+        self
+    }
+
+    /// Round frame times to at most `digits` decimal places when writing them. Defaults to `3`
+    /// (millisecond resolution).
+    pub fn with_frame_precision(mut self, digits: u32) -> Self {
+        self.frame_precision = digits;
+        self
+    }
+
+    /// Write the `ReferenceLongitude` and `ReferenceLatitude` global properties before the first
+    /// record, and subtract them from the (absolute) longitude and latitude of all written object
+    /// coordinates. Reference global properties written manually are not taken into account.
+    pub fn with_reference(mut self, longitude: f64, latitude: f64) -> Self {
+        self.reference = Some((longitude, latitude));
+        self
+    }
+
+    /// Do not write the trailing `|` of events without text, e.g. `0,Event=Destroyed|6a56`
+    /// instead of `0,Event=Destroyed|6a56|`, as some tools reject the empty field. Note that the
+    /// last param of such events is read back as their text.
+    pub fn with_compact_events(mut self) -> Self {
+        self.compact_events = true;
+        self
+    }
+
+    /// Write known country codes in uppercase (e.g. `Country=US`) and canonicalize coalitions
+    /// that only differ in casing from a known one (e.g. `allies` to `Allies`). Unknown countries
+    /// and other coalitions are written as is.
+    pub fn with_normalized_identifiers(mut self) -> Self {
+        self.normalize_identifiers = true;
+        self
+    }
+
     pub fn write(&mut self, record: impl Into<Record>) -> Result<(), io::Error> {
-        writeln!(self.wr, "{}", record.into())?;
+        let mut record = record.into();
+        if let Record::Event(event) = &mut record {
+            if self.compact_events && event.text.is_none() {
+                // written as the last field, thus without a trailing `|`
+                event.text = event.params.pop();
+            }
+        }
+        if let Some((longitude, latitude)) = self.reference {
+            if !self.reference_written {
+                self.reference_written = true;
+                writeln!(self.wr, "{}", GlobalProperty::ReferenceLongitude(longitude))?;
+                writeln!(self.wr, "{}", GlobalProperty::ReferenceLatitude(latitude))?;
+            }
+            if let Record::Update(update) = &mut record {
+                for prop in &mut update.props {
+                    if let Property::T(coords) = prop {
+                        if let Some(v) = &mut coords.longitude {
+                            *v -= longitude;
+                        }
+                        if let Some(v) = &mut coords.latitude {
+                            *v -= latitude;
+                        }
+                    }
+                }
+            }
+        }
+        if let Record::Frame(time) = record {
+            writeln!(self.wr, "#{}", time.max_precision(self.frame_precision))?;
+            return Ok(());
+        }
+        if let Record::Update(update) = &mut record {
+            if self.normalize_identifiers {
+                update.props.iter_mut().for_each(normalize_identifier);
+            }
+            if self.precision.is_some() || self.coords_precision.is_some() {
+                update.props = mem::take(&mut update.props)
+                    .into_iter()
+                    .map(|prop| match (prop, self.coords_precision) {
+                        (Property::T(coords), Some(precision)) => {
+                            Property::T(precision.apply(coords))
+                        }
+                        (prop, _) => match self.precision {
+                            Some(digits) => prop.max_precision(digits),
+                            None => prop,
+                        },
+                    })
+                    .collect();
+            }
+        }
+        match &record {
+            Record::Update(update) if self.normalize_identifiers => {
+                writeln!(self.wr, "{}", UppercaseCountries(update))?
+            }
+            record => writeln!(self.wr, "{record}")?,
+        }
+        Ok(())
+    }
+
+    /// Writes `text` as comment, prefixing each of its lines (ending at `\n`, `\r\n` or a bare
+    /// `\r`) with `//`. Comments are skipped when parsing the file.
+    pub fn write_comment(&mut self, text: &str) -> Result<(), io::Error> {
+        for line in lines::split_lines(text) {
+            if line.is_empty() {
+                writeln!(self.wr, "//")?;
+            } else if line.ends_with('\\') {
+                // a trailing backslash would continue the comment on the next line
+                writeln!(self.wr, "// {line} ")?;
+            } else {
+                writeln!(self.wr, "// {line}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes all records in order, stopping at the first error.
+    pub fn write_all<I>(&mut self, records: I) -> Result<(), io::Error>
+    where
+        I: IntoIterator,
+        I::Item: Into<Record>,
+    {
+        for record in records {
+            self.write(record)?;
+        }
         Ok(())
     }
 
@@ -37,3 +255,321 @@ where
         self.wr
     }
 }
+
+/// Canonicalizes coalitions that only differ in casing from a known one. Country codes are
+/// uppercased while formatting the update, see [`UppercaseCountries`].
+fn normalize_identifier(prop: &mut Property) {
+    if let Property::Coalition(Coalition::Unknown(name)) = prop {
+        for coalition in [Coalition::Allies, Coalition::Enemies, Coalition::Neutrals] {
+            if name.eq_ignore_ascii_case(&coalition.to_string()) {
+                *prop = Property::Coalition(coalition);
+                break;
+            }
+        }
+    }
+}
+
+/// Formats an update with the codes of known countries in uppercase (e.g. `Country=US`).
+struct UppercaseCountries<'a>(&'a Update);
+
+impl Display for UppercaseCountries<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt_with(f, |prop, f| match prop {
+            Property::Country(country) if !matches!(country, Country::Unknown(_)) => {
+                write!(f, "Country={}", country.code_upper())
+            }
+            prop => prop.fmt(f),
+        })
+    }
+}
+
+impl CoordsPrecision {
+    fn apply(&self, coords: Coords) -> Coords {
+        Coords {
+            longitude: coords.longitude.max_precision(self.lonlat),
+            latitude: coords.latitude.max_precision(self.lonlat),
+            altitude: coords.altitude.max_precision(self.alt),
+            u: coords.u.max_precision(self.alt),
+            v: coords.v.max_precision(self.alt),
+            roll: coords.roll.max_precision(self.angles),
+            pitch: coords.pitch.max_precision(self.angles),
+            yaw: coords.yaw.max_precision(self.angles),
+            heading: coords.heading.max_precision(self.angles),
+        }
+    }
+}
+
+#[test]
+fn test_precision() {
+    use crate::record::{Coords, Property, Update};
+
+    let mut wr = Writer::new(Vec::new()).unwrap().with_precision(1);
+    wr.write(Record::Frame(1.25)).unwrap();
+    wr.write(Update {
+        id: 1,
+        props: vec![
+            Property::T(Coords::default().position(1.23456, 2.34567, 345.678)),
+            Property::Slot(3),
+            Property::IAS(123.456),
+            Property::FuelWeight(1, 9.87),
+        ],
+    })
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(wr.into_inner()).unwrap(),
+        "FileType=text/acmi/tacview\nFileVersion=2.2\n#1.25\n1,T=2.3|1.2|345.7,Slot=3,IAS=123.5,FuelWeight2=9.9\n"
+    );
+}
+
+#[test]
+fn test_write_all() {
+    use crate::record::{Property, Update};
+
+    let mut wr = Writer::new(Vec::new()).unwrap();
+    wr.write_all([
+        Record::Frame(1.0),
+        Record::Update(Update {
+            id: 1,
+            props: vec![Property::HDG(90.0)],
+        }),
+        Record::Remove(2),
+    ])
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(wr.into_inner()).unwrap(),
+        "FileType=text/acmi/tacview\nFileVersion=2.2\n#1\n1,HDG=90\n-2\n"
+    );
+}
+
+#[test]
+fn test_compressed_entry_name() {
+    use std::io::{Cursor, Read};
+
+    let mut buf = Cursor::new(Vec::new());
+    Writer::new_compressed_named(&mut buf, "mission.txt.acmi")
+        .unwrap()
+        .write(Record::Frame(1.0))
+        .unwrap();
+
+    let mut archive = zip::ZipArchive::new(buf).unwrap();
+    let mut acmi = String::new();
+    archive
+        .by_name("mission.txt.acmi")
+        .unwrap()
+        .read_to_string(&mut acmi)
+        .unwrap();
+    assert_eq!(acmi, "FileType=text/acmi/tacview\nFileVersion=2.2\n#1\n");
+
+    for name in ["", "tracks/mission.txt.acmi", "tracks\\mission.txt.acmi"] {
+        let err = Writer::new_compressed_named(Cursor::new(Vec::new()), name)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn test_compression_options() {
+    use std::io::Cursor;
+
+    let mut buf = Cursor::new(Vec::new());
+    Writer::new_compressed_with_options(
+        &mut buf,
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+    )
+    .unwrap()
+    .write(Record::Frame(1.0))
+    .unwrap();
+
+    let mut archive = zip::ZipArchive::new(buf).unwrap();
+    let file = archive.by_name(DEFAULT_ENTRY_NAME).unwrap();
+    assert_eq!(file.compression(), CompressionMethod::Stored);
+}
+
+#[test]
+fn test_reference() {
+    use crate::record::{Coords, Update};
+    use crate::Parser;
+
+    let update = Update {
+        id: 1,
+        props: vec![Property::T(
+            Coords::default()
+                .position(42.5, 41.25, 1000.0)
+                .orientation(10.0, 20.0, 30.0),
+        )],
+    };
+    let mut wr = Writer::new(Vec::new()).unwrap().with_reference(41.0, 42.0);
+    wr.write(Record::Frame(0.0)).unwrap();
+    wr.write(update.clone()).unwrap();
+    let acmi = wr.into_inner();
+    assert_eq!(
+        String::from_utf8(acmi.clone()).unwrap(),
+        "FileType=text/acmi/tacview\nFileVersion=2.2\n0,ReferenceLongitude=41\n0,ReferenceLatitude=42\n#0\n1,T=0.25|0.5|1000|30|20|10\n"
+    );
+
+    let updates = Parser::new(acmi.as_slice())
+        .unwrap()
+        .with_reference_resolution()
+        .updates()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(updates, vec![update]);
+}
+
+#[test]
+fn test_frame_precision() {
+    use crate::Parser;
+
+    let mut wr = Writer::new(Vec::new()).unwrap();
+    wr.write_all((0..120).map(|i| Record::Frame(f64::from(i) / 60.0)))
+        .unwrap();
+    let frames = Parser::new(wr.into_inner().as_slice())
+        .unwrap()
+        .frames()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(frames.len(), 120);
+    assert!(frames.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(frames[1], 0.017);
+
+    let mut wr = Writer::new(Vec::new()).unwrap().with_frame_precision(1);
+    wr.write(Record::Frame(-1.25)).unwrap();
+    assert!(String::from_utf8(wr.into_inner())
+        .unwrap()
+        .ends_with("\n#-1.3\n"));
+}
+
+#[test]
+fn test_with_version() {
+    let wr = Writer::with_version(Vec::new(), 1).unwrap();
+    assert_eq!(
+        String::from_utf8(wr.into_inner()).unwrap(),
+        "FileType=text/acmi/tacview\nFileVersion=2.1\n"
+    );
+    assert_eq!(
+        Writer::with_version(Vec::new(), 3).err().unwrap().kind(),
+        io::ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn test_compact_events() {
+    use crate::record::{Event, EventKind};
+
+    let events = [
+        Event::builder(EventKind::Destroyed)
+            .source_id(0x6a56)
+            .build(),
+        Event::builder(EventKind::Landed).text("Landed").build(),
+    ];
+    let mut wr = Writer::new(Vec::new()).unwrap().with_compact_events();
+    wr.write_all(events.clone()).unwrap();
+    assert_eq!(
+        String::from_utf8(wr.into_inner()).unwrap(),
+        "FileType=text/acmi/tacview\nFileVersion=2.2\n0,Event=Destroyed|6a56\n0,Event=Landed|Landed\n"
+    );
+
+    let mut wr = Writer::new(Vec::new()).unwrap();
+    wr.write_all(events).unwrap();
+    assert_eq!(
+        String::from_utf8(wr.into_inner()).unwrap(),
+        "FileType=text/acmi/tacview\nFileVersion=2.2\n0,Event=Destroyed|6a56|\n0,Event=Landed|Landed\n"
+    );
+}
+
+#[test]
+fn test_coords_precision() {
+    use crate::record::Update;
+
+    let mut wr = Writer::new(Vec::new())
+        .unwrap()
+        .with_precision(1)
+        .with_coords_precision(CoordsPrecision {
+            lonlat: 7,
+            alt: 2,
+            angles: 0,
+        });
+    wr.write(Update {
+        id: 1,
+        props: vec![
+            Property::T(Coords::with_orientation(
+                1.123456789,
+                2.123456789,
+                345.678,
+                1.4,
+                2.6,
+                90.3,
+            )),
+            Property::IAS(123.456),
+        ],
+    })
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(wr.into_inner()).unwrap(),
+        "FileType=text/acmi/tacview\nFileVersion=2.2\n1,T=1.1234568|2.1234568|345.68|1|3|90,IAS=123.5\n"
+    );
+}
+
+#[test]
+fn test_normalized_identifiers() {
+    use crate::record::Update;
+
+    let update = Update {
+        id: 1,
+        props: vec![
+            Property::Country(Country::UnitedStates),
+            Property::Country(Country::Unknown("xx".to_string())),
+            Property::Coalition(Coalition::Unknown("ENEMIES".to_string())),
+            Property::Coalition(Coalition::Unknown("Blue Force".to_string())),
+        ],
+    };
+
+    let mut wr = Writer::new(Vec::new()).unwrap();
+    wr.write(update.clone()).unwrap();
+    assert!(String::from_utf8(wr.into_inner())
+        .unwrap()
+        .ends_with("\n1,Country=us,Country=xx,Coalition=ENEMIES,Coalition=Blue Force\n"));
+
+    let mut wr = Writer::new(Vec::new())
+        .unwrap()
+        .with_normalized_identifiers();
+    wr.write(update).unwrap();
+    assert!(String::from_utf8(wr.into_inner())
+        .unwrap()
+        .ends_with("\n1,Country=US,Country=xx,Coalition=Enemies,Coalition=Blue Force\n"));
+}
+
+#[test]
+fn test_write_comment() {
+    use crate::Parser;
+
+    let mut wr = Writer::new(Vec::new()).unwrap();
+    wr.write_comment("Exported by test\n\nC:\\tmp\\").unwrap();
+    wr.write(Record::Frame(1.0)).unwrap();
+    let acmi = String::from_utf8(wr.into_inner()).unwrap();
+    assert_eq!(
+        acmi,
+        "FileType=text/acmi/tacview\nFileVersion=2.2\n\
+         // Exported by test\n//\n// C:\\tmp\\ \n#1\n"
+    );
+
+    let records = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(records, vec![Record::Frame(1.0)]);
+
+    // a bare carriage return must not end the comment and start a record
+    let mut wr = Writer::new(Vec::new()).unwrap();
+    wr.write_comment("hello\r-1\r\nworld\r").unwrap();
+    wr.write(Record::Frame(1.0)).unwrap();
+    let acmi = String::from_utf8(wr.into_inner()).unwrap();
+    assert!(acmi.ends_with("\n// hello\n// -1\n// world\n#1\n"));
+
+    let records = Parser::new(acmi.as_bytes())
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(records, vec![Record::Frame(1.0)]);
+}